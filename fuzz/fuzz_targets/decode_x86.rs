@@ -0,0 +1,24 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linux_hunter_lib::memory::{
+	region::MemoryRegion,
+	x86::{decode, resolve_operand},
+};
+
+fuzz_target!(|data: &[u8]| {
+	if let Some(instruction) = decode(data, 0x1000) {
+		assert!(instruction.len <= data.len());
+		// the length can't depend on bytes after the instruction
+		assert_eq!(decode(&data[..instruction.len], 0x1000), Some(instruction));
+	}
+
+	let reader = [MemoryRegion::from_vec(
+		0x1000,
+		data.to_vec(),
+		"r-xp",
+		"",
+		"",
+	)];
+	let _ = resolve_operand(&reader[..], 0x1000, 1);
+});
@@ -0,0 +1,28 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linux_hunter_lib::memory::pattern::{
+	find_current_player_name, find_emetta, find_lobby_status, find_monster, find_player_buff,
+	find_player_damage, find_player_name, find_player_name_linux,
+};
+
+const FIND_FUNCS: [fn(&[u8]) -> Option<usize>; 8] = [
+	find_player_name,
+	find_current_player_name,
+	find_player_damage,
+	find_monster,
+	find_player_buff,
+	find_lobby_status,
+	find_emetta,
+	find_player_name_linux,
+];
+
+fuzz_target!(|data: &[u8]| {
+	for find in FIND_FUNCS {
+		if let Some(pos) = find(data) {
+			assert!(pos < data.len());
+			// searching from the match has to find the same match again
+			assert_eq!(find(&data[pos..]), Some(0));
+		}
+	}
+});
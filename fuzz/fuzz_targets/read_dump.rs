@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linux_hunter_lib::memory::dump::read_dump;
+
+fuzz_target!(|data: &[u8]| {
+	// only errors are allowed, no panics or huge allocations from corrupt lengths
+	let _ = read_dump(data);
+});
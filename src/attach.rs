@@ -0,0 +1,280 @@
+use crate::{conf::Config, MONSTER, PLAYER_DAMAGE, PLAYER_NAME_LINUX};
+use linux_hunter_lib::{
+	error::Error,
+	memory::{
+		get_memory_regions,
+		pattern::{all_pattern_getters, PatternGetter, PatternType},
+		preflight::test_read,
+		region::{verify_regions, MemoryRegion},
+	},
+	mhw::{
+		is_running,
+		process::{find_candidates, tied_candidates},
+	},
+};
+use nix::unistd::Pid;
+use std::{
+	sync::mpsc::{channel, Receiver, Sender},
+	thread::{self, sleep},
+	time::{Duration, Instant},
+};
+use sysinfo::System;
+use tracing::{debug, info, warn};
+
+const DETECT_INTERVAL: Duration = Duration::from_millis(200);
+// the game needs a while after launching until all patterns are in memory
+const RESCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub enum AttachEvent {
+	Detecting,
+	Scanning(Pid),
+	RegionsRead {
+		done: usize,
+		total: usize,
+	},
+	PatternSearched {
+		pattern_type: PatternType,
+		found: bool,
+	},
+	Attached(Pid, Box<[PatternGetter; 8]>),
+	Failed(anyhow::Error),
+}
+
+/// tries to find the MH:W pid, giving up after `max_attempts` or never if it is `None`
+pub fn detect_pid(max_attempts: Option<usize>) -> Result<Pid, Error> {
+	info!("Trying to detect MHW PID");
+	let mut attempts = 0;
+	loop {
+		let found = find_candidates().and_then(|candidates| {
+			// only reported for the pid that is returned, not on every attempt
+			if let Some((first, second)) = tied_candidates(&candidates) {
+				warn!(
+					"processes {} and {} look equally like the game, using the bigger one. pass --mhw-pid if that's wrong",
+					first.pid, second.pid
+				);
+			}
+			candidates
+				.first()
+				.map(|candidate| candidate.pid)
+				.ok_or(Error::ProcessNotFound)
+		});
+
+		match found {
+			Ok(pid) => {
+				info!("Found pid: {}", pid);
+				return Ok(pid);
+			}
+
+			Err(e) => {
+				attempts += 1;
+				if max_attempts.is_some_and(|max| attempts > max) {
+					return Err(e);
+				}
+				sleep(DETECT_INTERVAL);
+			}
+		}
+	}
+}
+
+// sending only fails if the app exited in the meantime, so all send errors are ignored
+fn send_progress(progress: Option<&Sender<AttachEvent>>, event: AttachEvent) {
+	if let Some(progress) = progress {
+		let _ = progress.send(event);
+	}
+}
+
+/// reads all memory regions of the game, or the regions of the dump if one should be loaded
+pub fn read_regions(
+	mhw_pid: Pid,
+	conf: &Config,
+	progress: Option<&Sender<AttachEvent>>,
+) -> anyhow::Result<Vec<MemoryRegion>> {
+	let mut regions = get_memory_regions(mhw_pid, conf.load_dump.as_deref())?;
+	verify_regions(&regions)?;
+
+	let total = regions.len();
+	for (i, region) in regions.iter_mut().enumerate() {
+		if let Err(e) = region.fill_data(mhw_pid) {
+			warn!("Failed to fill region data: {}\n{}\n", e, region.debug_info)
+		}
+		send_progress(progress, AttachEvent::RegionsRead { done: i + 1, total });
+	}
+
+	Ok(regions)
+}
+
+/// searches the regions for all known patterns
+pub fn search_regions(
+	regions: &[MemoryRegion],
+	progress: Option<&Sender<AttachEvent>>,
+) -> [PatternGetter; 8] {
+	let mut pattern_getters = all_pattern_getters();
+
+	for get_pattern in &mut pattern_getters {
+		for (i, region) in regions.iter().enumerate() {
+			if region.data.is_some() && get_pattern.search(region).is_ok() {
+				debug!(
+					"found pattern '{:X?}' in region {:X}",
+					get_pattern.pattern_type, i
+				);
+			}
+		}
+
+		get_pattern.apply_rules(regions);
+		if get_pattern.is_ambiguous() {
+			warn!(
+				"pattern {:?} is ambiguous: {} matches passed its rules, expected {}",
+				get_pattern.pattern_type,
+				get_pattern.accepted().count(),
+				get_pattern.expected_count()
+			);
+		}
+
+		send_progress(
+			progress,
+			AttachEvent::PatternSearched {
+				pattern_type: get_pattern.pattern_type,
+				found: get_pattern.mem_location.is_some(),
+			},
+		);
+	}
+
+	pattern_getters
+}
+
+/// reads the memory of the game and searches it for all known patterns, without checking if the required ones were found
+pub fn search_patterns(
+	mhw_pid: Pid,
+	conf: &Config,
+	progress: Option<&Sender<AttachEvent>>,
+) -> anyhow::Result<[PatternGetter; 8]> {
+	let start = Instant::now();
+
+	info!("finding main AoB entry points...");
+
+	let regions = read_regions(mhw_pid, conf, progress)?;
+	let pattern_getters = search_regions(&regions, progress);
+
+	if conf.debug() {
+		debug!("took {}ms", start.elapsed().as_millis());
+
+		let sys = System::new_all();
+		let pid = sysinfo::get_current_pid().unwrap();
+		if let Some(process) = sys.processes().get(&pid) {
+			debug!("Memory usage: {}kb", process.memory() / 1024);
+		}
+	}
+
+	info!("Done");
+
+	if conf.debug() {
+		for pg in &pattern_getters {
+			debug!(
+				"\n{:?}:\n Found: {}\n MemoryLocation: {}",
+				pg.pattern_type,
+				pg.mem_location.is_some(),
+				match pg.mem_location {
+					Some(loc) => loc.to_string(),
+					None => "None".to_string(),
+				}
+			);
+		}
+	}
+
+	// the ~3gb of memory regions get dropped here, since we will use direct memory access to get the data
+	Ok(pattern_getters)
+}
+
+/// like `search_patterns`, but fails if any of the patterns needed to show the TUI weren't found
+pub fn scan_patterns(
+	mhw_pid: Pid,
+	conf: &Config,
+	progress: Option<&Sender<AttachEvent>>,
+) -> anyhow::Result<[PatternGetter; 8]> {
+	let pattern_getters = search_patterns(mhw_pid, conf, progress)?;
+
+	if pattern_getters[PLAYER_NAME_LINUX].mem_location.is_none() {
+		return Err(Error::PatternNotFound(PatternType::PlayerNameLinux).into());
+	}
+
+	if pattern_getters[PLAYER_DAMAGE].mem_location.is_none() {
+		return Err(Error::PatternNotFound(PatternType::PlayerDamage).into());
+	}
+
+	if conf.run.show_monsters && pattern_getters[MONSTER].mem_location.is_none() {
+		return Err(Error::PatternNotFound(PatternType::Monsters).into());
+	}
+
+	Ok(pattern_getters)
+}
+
+/// gets the pid of the game for the commands that only need it once, `0` when reading from a dump
+pub fn resolve_pid(conf: &Config) -> Result<Pid, Error> {
+	match (conf.mhw_pid, &conf.load_dump) {
+		(Some(pid), _) => Ok(Pid::from_raw(pid)),
+		(None, Some(_)) => Ok(Pid::from_raw(0)),
+		(None, None) => detect_pid(Some(50)),
+	}
+}
+
+/// finds and scans the game in the background, reporting the progress through the returned channel.
+/// with `retry` it keeps waiting for the game and re-scanning until it succeeds, otherwise the first
+/// failure is reported as `AttachEvent::Failed`
+pub fn spawn_attach(mut conf: Config, retry: bool) -> Receiver<AttachEvent> {
+	// a dump can't change, so scanning it again won't help
+	let retry = retry && conf.load_dump.is_none();
+
+	let (tx, rx) = channel();
+
+	// sending only fails if the app exited in the meantime, so all send errors are ignored
+	thread::spawn(move || loop {
+		let _ = tx.send(AttachEvent::Detecting);
+
+		let pid = match (conf.mhw_pid, &conf.load_dump) {
+			(Some(pid), _) => Pid::from_raw(pid),
+			(None, Some(_)) => Pid::from_raw(0),
+			(None, None) => match detect_pid(if retry { None } else { Some(50) }) {
+				Ok(pid) => pid,
+				Err(e) => {
+					let _ = tx.send(AttachEvent::Failed(e.into()));
+					return;
+				}
+			},
+		};
+
+		// scanning would fail for every single region anyways, and retrying won't fix permissions
+		if conf.load_dump.is_none() {
+			if let Err(e @ Error::PermissionDenied { .. }) = test_read(pid) {
+				let _ = tx.send(AttachEvent::Failed(e.into()));
+				return;
+			}
+		}
+
+		loop {
+			let _ = tx.send(AttachEvent::Scanning(pid));
+
+			match scan_patterns(pid, &conf, Some(&tx)) {
+				Ok(patterns) => {
+					let _ = tx.send(AttachEvent::Attached(pid, Box::new(patterns)));
+					return;
+				}
+				Err(e) if retry => {
+					warn!("failed to scan the game, retrying: {}", e);
+					if !is_running(pid) {
+						// the given pid is gone for good, look for the new one instead
+						conf.mhw_pid = None;
+						break;
+					}
+					sleep(RESCAN_INTERVAL);
+				}
+				Err(e) => {
+					let _ = tx.send(AttachEvent::Failed(e));
+					return;
+				}
+			}
+		}
+	});
+
+	rx
+}
@@ -0,0 +1,362 @@
+use crate::{
+	attach::{read_regions, resolve_pid, search_patterns, search_regions},
+	conf::{Config, DumpArgs, InspectArgs, ScanArgs, ValueType},
+};
+use linux_hunter_lib::{
+	memory::{
+		dump::{
+			clip_to_regions, dump_target, partial_ranges, persist_dump, read_dump_header,
+			DumpHeader, DumpWriter,
+		},
+		fuzzy::{fuzzy_search, FuzzyOptions},
+		get_memory_regions, mapped_regions,
+		pattern::{PatternGetter, Rule},
+		preflight::{run_checks, Check, CheckStatus},
+		region::{follow_pointers, read_memory, verify_regions, MemoryRegion},
+		x86::resolve_operand,
+	},
+	mhw::{
+		find_mhw_pid,
+		process::{find_candidates, tied_candidates},
+	},
+};
+use nix::unistd::Pid;
+use std::{
+	fs::{create_dir_all, read_to_string, remove_file},
+	path::{Path, PathBuf},
+};
+use tracing::warn;
+
+pub fn list_processes() -> anyhow::Result<()> {
+	let candidates = find_candidates()?;
+	if candidates.is_empty() {
+		return Err(anyhow::anyhow!("No MH:W process found"));
+	}
+
+	println!(
+		"{:>8} {:>5} {:<16} {:<50} CMDLINE",
+		"PID", "SCORE", "COMM", "EVIDENCE"
+	);
+	for candidate in &candidates {
+		println!("{}", candidate);
+	}
+
+	if let Some((first, second)) = tied_candidates(&candidates) {
+		warn!(
+			"processes {} and {} look equally like the game, the bigger one is used. pass --mhw-pid if that's wrong",
+			first.pid, second.pid
+		);
+	}
+
+	Ok(())
+}
+
+pub fn print_checks(checks: &[Check]) {
+	for check in checks {
+		println!("[{:>4}] {}: {}", check.status, check.name, check.message);
+		if let Some(hint) = &check.hint {
+			println!("       hint: {}", hint);
+		}
+	}
+}
+
+pub fn doctor(conf: &Config) -> anyhow::Result<()> {
+	let pid = match conf.mhw_pid {
+		Some(pid) => Some(Pid::from_raw(pid)),
+		None => find_mhw_pid().ok(),
+	};
+
+	let checks = run_checks(pid);
+	print_checks(&checks);
+
+	if checks.iter().any(|c| c.status == CheckStatus::Fail) {
+		return Err(anyhow::anyhow!("Some checks failed"));
+	}
+
+	Ok(())
+}
+
+// the maps of the game or the dump, to check where the rip-relative operands point to
+fn maps_of(conf: &Config, pid: Pid) -> Option<String> {
+	match &conf.load_dump {
+		Some(dump) => read_dump_header(Path::new(dump.as_ref()))
+			.ok()
+			.map(|header| header.maps),
+		None => read_to_string(format!("/proc/{}/maps", pid)).ok(),
+	}
+}
+
+fn describe_target(target: usize, mapped: Option<&[MemoryRegion]>) -> String {
+	let Some(mapped) = mapped else {
+		return String::from("(maps unknown)");
+	};
+
+	match mapped
+		.iter()
+		.find(|r| r.get_begin() <= target && target < r.get_end())
+	{
+		Some(region) if region.permissions.starts_with('r') => {
+			format!("mapped ({} {})", region.permissions, region.path)
+		}
+		Some(region) => format!("NOT READABLE ({})", region.permissions),
+		None => String::from("NOT MAPPED"),
+	}
+}
+
+fn print_report(pattern_getters: &[PatternGetter], maps: Option<&str>) {
+	// some strings match thousands of times
+	const MAX_MATCHES: usize = 10;
+
+	let mapped = maps.map(mapped_regions);
+	let (mut broken, mut ambiguous) = (0, 0);
+
+	for pg in pattern_getters {
+		let status = match (pg.matches.len(), pg.accepted().count()) {
+			(0, _) => "NOT FOUND",
+			(_, 0) => "NO MATCH PASSED THE RULES",
+			_ if pg.is_ambiguous() => "AMBIGUOUS",
+			_ => "OK",
+		};
+		match status {
+			"OK" => (),
+			"AMBIGUOUS" => ambiguous += 1,
+			_ => broken += 1,
+		}
+
+		println!(
+			"{:?}: {} ({} matches, {} accepted, {} expected)",
+			pg.pattern_type,
+			status,
+			pg.matches.len(),
+			pg.accepted().count(),
+			pg.expected_count()
+		);
+
+		for m in pg.matches.iter().take(MAX_MATCHES) {
+			let used = pg
+				.mem_location
+				.is_some_and(|loc| loc.address == m.location.address);
+			let path = match m.path.is_empty() {
+				true => "[anonymous]",
+				false => &m.path,
+			};
+
+			print!(
+				"  {} 0x{:X} {} {}",
+				if used { "*" } else { " " },
+				m.location.address,
+				m.permissions,
+				path
+			);
+			if !m.rejected.is_empty() {
+				print!(", rejected by {:?}", m.rejected);
+			}
+			if let Some(target) = m.target {
+				print!(
+					", points to 0x{:X} {}",
+					target,
+					describe_target(target, mapped.as_deref())
+				);
+			}
+			println!();
+		}
+		if pg.matches.len() > MAX_MATCHES {
+			println!("    ... and {} more", pg.matches.len() - MAX_MATCHES);
+		}
+	}
+
+	println!(
+		"\n{} of {} signatures are broken, {} are ambiguous",
+		broken,
+		pattern_getters.len(),
+		ambiguous
+	);
+}
+
+// lists the likely new locations of the signatures without accepted matches
+fn print_fuzzy(pattern_getters: &[PatternGetter], regions: &[MemoryRegion], opts: &FuzzyOptions) {
+	const MAX_CANDIDATES: usize = 5;
+
+	for pg in pattern_getters {
+		if pg.accepted().next().is_some() {
+			continue;
+		}
+
+		// code signatures can only have moved to other code
+		let code_only = pg.rules.contains(&Rule::Executable);
+		let mut candidates: Vec<_> = regions
+			.iter()
+			.filter(|r| !code_only || r.permissions.contains('x'))
+			.flat_map(|r| fuzzy_search(pg.pattern_type.signature(), r, opts))
+			.collect();
+		candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+		println!(
+			"\n{:?}: {} fuzzy candidates",
+			pg.pattern_type,
+			candidates.len()
+		);
+		for candidate in candidates.iter().take(MAX_CANDIDATES) {
+			println!(
+				"  0x{:X} {:.0}% ({} mismatches, {} shifted wildcard groups)\n    {}",
+				candidate.address,
+				candidate.confidence * 100.0,
+				candidate.mismatches,
+				candidate.shifts,
+				candidate.signature
+			);
+		}
+	}
+}
+
+pub fn scan(conf: &Config, args: &ScanArgs) -> anyhow::Result<()> {
+	if let Some(dump) = &conf.load_dump {
+		let path = Path::new(dump.as_ref());
+		if path.is_file() {
+			let header = read_dump_header(path)?;
+			println!(
+				"dump of pid {} taken at {} (game build {})",
+				header.pid,
+				header.timestamp,
+				header.game_build.as_deref().unwrap_or("unknown")
+			);
+		}
+	}
+
+	let pid = resolve_pid(conf)?;
+	let regions = read_regions(pid, conf, None)?;
+	let pattern_getters = search_regions(&regions, None);
+
+	if args.report {
+		print_report(&pattern_getters, maps_of(conf, pid).as_deref());
+	} else {
+		for pg in &pattern_getters {
+			match pg.mem_location {
+				Some(loc) => println!(
+					"{:?}: found ({}), {} of {} matches accepted{}",
+					pg.pattern_type,
+					loc,
+					pg.accepted().count(),
+					pg.matches.len(),
+					if pg.is_ambiguous() { ", AMBIGUOUS" } else { "" }
+				),
+				None => println!("{:?}: not found", pg.pattern_type),
+			}
+		}
+	}
+
+	if args.fuzzy {
+		let opts = FuzzyOptions {
+			max_mismatches: args.max_mismatches,
+			max_shift: args.max_shift,
+		};
+		print_fuzzy(&pattern_getters, &regions, &opts);
+	}
+
+	Ok(())
+}
+
+fn default_dump_dir() -> anyhow::Result<PathBuf> {
+	let mut dir = dirs::cache_dir().ok_or(anyhow::anyhow!("Failed to get cache dir"))?;
+	dir.push("linux-hunter-rs");
+	dir.push("dumps");
+	create_dir_all(&dir)?;
+	Ok(dir)
+}
+
+pub fn dump(conf: &Config, args: &DumpArgs) -> anyhow::Result<()> {
+	let pid = resolve_pid(conf)?;
+
+	let mut header = DumpHeader::new(pid)?;
+	header.partial = args.partial;
+	let path = match &args.path {
+		Some(path) => PathBuf::from(path.as_ref()),
+		None => default_dump_dir()?,
+	};
+	let target = dump_target(&path, args.overwrite, header.timestamp)?;
+
+	let mut regions = get_memory_regions(pid, None)?;
+	verify_regions(&regions)?;
+
+	if args.partial {
+		let patterns = search_patterns(pid, conf, None)?;
+		let ranges = partial_ranges(&pid, &patterns, args.window, args.chain_len);
+		regions = clip_to_regions(&ranges, &regions);
+	}
+
+	// write to a temporary file first, so a failed dump never replaces an existing one
+	let mut tmp = target.clone().into_os_string();
+	tmp.push(".tmp");
+	let tmp = PathBuf::from(tmp);
+
+	let mut writer = DumpWriter::create(&tmp, &header)?;
+
+	for region in &mut regions {
+		let res = match region.fill_data(pid) {
+			Ok(_) => writer.write_region(region),
+			Err(e) => {
+				warn!("Failed to fill region data: {}\n{}\n", e, region.debug_info);
+				Ok(())
+			}
+		};
+		// only keep one region in memory at a time
+		region.data = None;
+
+		if let Err(e) = res {
+			let _ = remove_file(&tmp);
+			return Err(e.into());
+		}
+	}
+
+	let written = writer.regions;
+	writer.finish()?;
+	if let Err(e) = persist_dump(&tmp, &target, args.overwrite) {
+		let _ = remove_file(&tmp);
+		return Err(e.into());
+	}
+
+	println!("Dumped {} regions to {}", written, target.display());
+
+	Ok(())
+}
+
+pub fn inspect(conf: &Config, args: &InspectArgs) -> anyhow::Result<()> {
+	let pid = resolve_pid(conf)?;
+
+	let mut addr = args.address;
+	if args.rip_relative {
+		addr = resolve_operand(&pid, addr, args.operand)?;
+	}
+	let addr = follow_pointers(&pid, addr, &args.offsets)?;
+
+	let len = match args.value_type {
+		ValueType::U8 => 1,
+		ValueType::U16 => 2,
+		ValueType::U32 | ValueType::I32 | ValueType::F32 => 4,
+		ValueType::U64 | ValueType::I64 | ValueType::F64 => 8,
+		ValueType::Str | ValueType::Bytes => args.len,
+	};
+	let mem = read_memory(pid, addr, len)?;
+
+	// the lengths above always match the sizes of the types
+	let value = match args.value_type {
+		ValueType::U8 => mem[0].to_string(),
+		ValueType::U16 => u16::from_le_bytes(mem[..].try_into().unwrap()).to_string(),
+		ValueType::U32 => u32::from_le_bytes(mem[..].try_into().unwrap()).to_string(),
+		ValueType::U64 => u64::from_le_bytes(mem[..].try_into().unwrap()).to_string(),
+		ValueType::I32 => i32::from_le_bytes(mem[..].try_into().unwrap()).to_string(),
+		ValueType::I64 => i64::from_le_bytes(mem[..].try_into().unwrap()).to_string(),
+		ValueType::F32 => f32::from_le_bytes(mem[..].try_into().unwrap()).to_string(),
+		ValueType::F64 => f64::from_le_bytes(mem[..].try_into().unwrap()).to_string(),
+		ValueType::Str => {
+			// strings in the game are nul terminated
+			let end = mem.iter().position(|b| *b == 0).unwrap_or(mem.len());
+			format!("{:?}", String::from_utf8_lossy(&mem[..end]))
+		}
+		ValueType::Bytes => format!("{:02X?}", mem),
+	};
+
+	println!("0x{:X}: {}", addr, value);
+
+	Ok(())
+}
@@ -1,9 +1,112 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify};
+use ratatui::style::{Color, Style};
+use serde::Deserialize;
+use std::{
+	collections::HashMap,
+	ffi::OsString,
+	fs, io,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
 use tracing::Level;
 
-#[derive(Parser, Debug)]
+const CONFIG_FILE: &str = "linux-hunter-rs/config.toml";
+const CROWNS_FILE: &str = "linux-hunter-rs/crowns.json";
+const DEFAULT_CHARACTER: &str = "default";
+
+#[derive(Parser, Debug, Clone)]
 #[command(version, about, long_about = None)]
 pub struct Config {
+	#[command(subcommand)]
+	pub command: Option<Command>,
+
+	// running without a subcommand is the same as `run`
+	#[command(flatten)]
+	pub run: RunArgs,
+
+	#[arg(
+		long,
+		global = true,
+		help = "Specifies which pid to scan memory for (usually main MH:W). When not specified, linux-hunter-rs will try to find it automatically"
+	)]
+	pub mhw_pid: Option<i32>,
+
+	#[arg(
+		short,
+		long,
+		global = true,
+		help = "Manually set the log level (trace, debug, info, warn, error). Defaults to info"
+	)]
+	pub log_level: Option<Level>,
+
+	#[arg(
+		short,
+		long,
+		global = true,
+		help = "Selects a profile from the config file, overriding the one set in the file"
+	)]
+	pub profile: Option<Box<str>>,
+
+	#[arg(
+		long,
+		global = true,
+		help = "Sets the path to the config file. Defaults to ~/.config/linux-hunter-rs/config.toml"
+	)]
+	pub config: Option<Box<str>>,
+
+	#[arg(
+		long,
+		global = true,
+		help = "Sets the path to the log file. Defaults to ~/.cache/linux-hunter-rs.log"
+	)]
+	pub log_file: Option<Box<str>>,
+
+	// set by the `replay` and `scan --dump` subcommands
+	#[arg(skip)]
+	pub load_dump: Option<Box<str>>,
+
+	// only set through the config file
+	#[arg(skip)]
+	pub colors: Colors,
+
+	// the run args as they were given on the command line, to re-apply the profile on top of when reloading
+	#[arg(skip)]
+	cli_run: RunArgs,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+	/// Shows the TUI for the running game (default)
+	Run(RunArgs),
+
+	/// Scans the game for all patterns, reports where they were found and exits
+	Scan(ScanArgs),
+
+	/// Dumps the memory of the game into a single compressed file. Very slow, but useful for debugging
+	Dump(DumpArgs),
+
+	/// Reads a value from the memory of the game
+	Inspect(InspectArgs),
+
+	/// Shows the TUI for a previously dumped memory dump
+	Replay {
+		#[arg(help = "The dump file, or the directory of a dump from an older version")]
+		path: Box<str>,
+
+		#[command(flatten)]
+		run: RunArgs,
+	},
+
+	/// Checks if the memory of MH:W can be read and prints hints on how to fix it if not
+	Doctor,
+
+	/// Lists all processes that look like MH:W, the one that would be picked first
+	ListProcesses,
+}
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct RunArgs {
 	#[arg(
 		short = 'm',
 		long,
@@ -19,54 +122,451 @@ pub struct Config {
 	pub show_crowns: bool,
 
 	#[arg(
+		short = 'w',
 		long,
-		help = "Specifies which pid to scan memory for (usually main MH:W). When not specified, linux-hunter-rs will try to find it automatically"
+		help = "Keeps waiting for MH:W to start instead of giving up after ~10 seconds"
 	)]
-	pub mhw_pid: Option<i32>,
+	pub wait: bool,
 
 	#[arg(
 		short = 'r',
 		long,
-		help = "Specifies what is the UI/stats refresh interval in ms. If unspecified it will try to refresh at 60 fps (16.66ms)"
+		help = "Specifies what is the UI refresh interval in ms. If unspecified it will try to refresh at 60 fps (16.66ms)"
 	)]
 	pub refresh: Option<f64>,
 
 	#[arg(
 		long,
-		help = "Dumps memory to a file in the dir specified upon initialization. WARNING: very slow, memory hungry AND DELTES ALL CONTENTS OF THAT DIRECTORY, but useful for debugging"
+		help = "How often the memory of the game is read per second, independent of the UI refresh. Defaults to 30"
 	)]
-	pub dump_mem: Option<Box<str>>,
+	pub poll_rate: Option<f64>,
 
-	#[arg(long, help = "Loads a previously dumped memory dump")]
-	pub load_dump: Option<Box<str>>,
-
-	#[arg(long, help = "Shows how long it took to construct a frame in the tui")]
+	#[arg(
+		long,
+		help = "Shows how long it took to read the memory and to draw a frame in the tui"
+	)]
 	pub show_frametime: bool,
 
+	#[arg(
+		long,
+		help = "Writes the game data, including monster sizes and crown thresholds, as json to this file whenever it changes"
+	)]
+	pub export: Option<Box<str>>,
+
+	#[arg(
+		long,
+		help = "The character whose crowns are tracked until the game tells who is playing [default: default]"
+	)]
+	pub character: Option<String>,
+}
+
+impl RunArgs {
+	// the args given after a subcommand, with the ones given before it filling in what they left out
+	fn merge(&self, before: &RunArgs) -> RunArgs {
+		RunArgs {
+			show_monsters: self.show_monsters || before.show_monsters,
+			show_crowns: self.show_crowns || before.show_crowns,
+			wait: self.wait || before.wait,
+			refresh: self.refresh.or(before.refresh),
+			poll_rate: self.poll_rate.or(before.poll_rate),
+			show_frametime: self.show_frametime || before.show_frametime,
+			export: self.export.clone().or(before.export.clone()),
+			character: self.character.clone().or(before.character.clone()),
+		}
+	}
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ScanArgs {
+	#[arg(
+		long,
+		help = "Scans a previously dumped memory dump instead of the game"
+	)]
+	pub dump: Option<Box<str>>,
+
+	#[arg(
+		long,
+		help = "Lists every match with its region and where its pointer leads, to see which signatures broke after a game update"
+	)]
+	pub report: bool,
+
+	#[arg(
+		long,
+		help = "Searches for the likely new locations of the signatures that weren't found, with a suggested new signature for each"
+	)]
+	pub fuzzy: bool,
+
+	#[arg(
+		long,
+		default_value_t = 4,
+		requires = "fuzzy",
+		help = "How many known bytes of a signature may differ in a fuzzy match"
+	)]
+	pub max_mismatches: usize,
+
+	#[arg(
+		long,
+		default_value_t = 4,
+		requires = "fuzzy",
+		help = "By how many bytes each group of wildcards may grow or shrink in a fuzzy match"
+	)]
+	pub max_shift: usize,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DumpArgs {
+	#[arg(
+		help = "The file to dump to. A directory gets a new timestamped dump file [default: <cache dir>/linux-hunter-rs/dumps]"
+	)]
+	pub path: Option<Box<str>>,
+
+	#[arg(long, help = "Replace the file if it is an existing dump")]
+	pub overwrite: bool,
+
+	#[arg(
+		long,
+		help = "Only dump the memory around the found patterns and what update_all reads, small enough for bug reports"
+	)]
+	pub partial: bool,
+
+	#[arg(
+		long,
+		value_parser = parse_address,
+		default_value = "0x1000",
+		requires = "partial",
+		help = "Bytes to dump before and after each found pattern"
+	)]
+	pub window: usize,
+
+	#[arg(
+		long,
+		value_parser = parse_address,
+		default_value = "0x1000",
+		requires = "partial",
+		help = "Bytes to dump at the targets of the lobby, damage and monster pointers"
+	)]
+	pub chain_len: usize,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct InspectArgs {
+	#[arg(value_parser = parse_address, help = "The address to read, either hex (0x...) or decimal")]
+	pub address: usize,
+
 	#[arg(
 		short,
 		long,
-		help = "Manually set the log level (trace, debug, info, warn, error).",
-		default_value = "info"
+		value_delimiter = ',',
+		value_parser = parse_address,
+		help = "Pointer chain to follow: for each offset the pointer at the current address is read and the offset added to it"
 	)]
-	pub log_level: Level,
+	pub offsets: Vec<usize>,
 
 	#[arg(
 		long,
-		help = "Sets the path to the log file. Defaults to ~/.cache/linux-hunter-rs.log"
+		help = "Treats the address as the start of instructions like 'mov r64,[rip+d32]' or 'call rel32' and starts from the address the first operand leads to"
 	)]
-	pub log_file: Option<Box<str>>,
+	pub rip_relative: bool,
+
+	#[arg(
+		long,
+		default_value_t = 0,
+		requires = "rip_relative",
+		help = "Which rip-relative operand to start from, counting from 0"
+	)]
+	pub operand: usize,
+
+	#[arg(
+		short = 't',
+		long = "type",
+		value_enum,
+		default_value = "u32",
+		help = "How to interpret the value"
+	)]
+	pub value_type: ValueType,
+
+	#[arg(
+		long,
+		default_value = "32",
+		help = "The length in bytes for the str and bytes types"
+	)]
+	pub len: usize,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum ValueType {
+	U8,
+	U16,
+	U32,
+	U64,
+	I32,
+	I64,
+	F32,
+	F64,
+	Str,
+	Bytes,
+}
+
+fn parse_address(s: &str) -> Result<usize, String> {
+	match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+		Some(hex) => usize::from_str_radix(hex, 16),
+		None => s.parse(),
+	}
+	.map_err(|e| format!("invalid address '{}': {}", s, e))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Colors {
+	pub gauge: Color,
+	pub background: Color,
+	// monster gauges once the monster can be captured
+	pub capture: Color,
+}
+
+impl Default for Colors {
+	fn default() -> Self {
+		Self {
+			gauge: Color::White,
+			background: Color::Black,
+			capture: Color::Yellow,
+		}
+	}
+}
+
+impl Colors {
+	pub fn gauge_style(&self) -> Style {
+		Style::new().fg(self.gauge).bg(self.background)
+	}
+
+	pub fn capture_style(&self) -> Style {
+		Style::new().fg(self.capture).bg(self.background)
+	}
+}
+
+/// the settings a profile in the config file can contain, all of them are optional
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+	pub show_monsters: Option<bool>,
+	pub show_crowns: Option<bool>,
+	pub show_frametime: Option<bool>,
+	pub wait: Option<bool>,
+	pub refresh: Option<f64>,
+	pub poll_rate: Option<f64>,
+	pub character: Option<String>,
+	pub export: Option<Box<str>>,
+	pub log_level: Option<String>,
+	pub gauge_color: Option<String>,
+	pub background_color: Option<String>,
+	pub capture_color: Option<String>,
+}
+
+impl Profile {
+	// values set in `other` take precedence
+	fn merge(self, other: Profile) -> Profile {
+		Profile {
+			show_monsters: other.show_monsters.or(self.show_monsters),
+			show_crowns: other.show_crowns.or(self.show_crowns),
+			show_frametime: other.show_frametime.or(self.show_frametime),
+			wait: other.wait.or(self.wait),
+			refresh: other.refresh.or(self.refresh),
+			poll_rate: other.poll_rate.or(self.poll_rate),
+			character: other.character.or(self.character),
+			export: other.export.or(self.export),
+			log_level: other.log_level.or(self.log_level),
+			gauge_color: other.gauge_color.or(self.gauge_color),
+			background_color: other.background_color.or(self.background_color),
+			capture_color: other.capture_color.or(self.capture_color),
+		}
+	}
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+	// the profile to use when none is given on the command line
+	profile: Option<String>,
+	// settings for all profiles
+	defaults: Profile,
+	profiles: HashMap<String, Profile>,
+}
+
+fn parse_color(color: &str) -> Result<Color, String> {
+	Color::from_str(color).map_err(|_| format!("invalid color '{}'", color))
 }
 
 impl Config {
 	pub fn debug(&self) -> bool {
-		self.log_level > Level::INFO
+		self.log_level() > Level::INFO
+	}
+
+	pub fn log_level(&self) -> Level {
+		self.log_level.unwrap_or(Level::INFO)
+	}
+
+	pub fn config_path(&self) -> Option<PathBuf> {
+		match &self.config {
+			Some(path) => Some(PathBuf::from(&**path)),
+			None => dirs::config_dir().map(|dir| dir.join(CONFIG_FILE)),
+		}
+	}
+
+	pub fn character(&self) -> &str {
+		self.run.character.as_deref().unwrap_or(DEFAULT_CHARACTER)
+	}
+
+	pub fn crowns_path(&self) -> Option<PathBuf> {
+		dirs::data_dir().map(|dir| dir.join(CROWNS_FILE))
+	}
+
+	/// reads the config file and merges the selected profile into its defaults.
+	/// a missing file is not an error, unless a profile was explicitly selected
+	pub fn load_profile(&self) -> Result<Profile, String> {
+		let path = match self.config_path() {
+			Some(path) => path,
+			None => return Ok(Profile::default()),
+		};
+
+		let file: ConfigFile = match fs::read_to_string(&path) {
+			Ok(content) => toml::from_str(&content)
+				.map_err(|e| format!("invalid config file {}: {}", path.display(), e))?,
+			Err(e) if e.kind() == io::ErrorKind::NotFound && self.config.is_none() => {
+				ConfigFile::default()
+			}
+			Err(e) => return Err(format!("can't read config file {}: {}", path.display(), e)),
+		};
+
+		let name = match self.profile.as_deref().or(file.profile.as_deref()) {
+			Some(name) => name,
+			None => return Ok(file.defaults),
+		};
+
+		match file.profiles.get(name) {
+			Some(profile) => Ok(file.defaults.merge(profile.clone())),
+			None => Err(format!("unknown profile '{}'", name)),
+		}
+	}
+
+	/// applies the settings of a profile, but only where they weren't given on the command line
+	pub fn apply_profile(&mut self, profile: &Profile) -> Result<(), String> {
+		// flags can only be turned on from the command line, so one that is off leaves it to the profile,
+		// which may turn off again what its defaults turned on
+		let flag = |cli: bool, profile: Option<bool>| cli || profile.unwrap_or(cli);
+
+		let run = &mut self.run;
+		run.show_monsters = flag(run.show_monsters, profile.show_monsters);
+		run.show_crowns = flag(run.show_crowns, profile.show_crowns);
+		run.show_frametime = flag(run.show_frametime, profile.show_frametime);
+		run.wait = flag(run.wait, profile.wait);
+		run.refresh = run.refresh.or(profile.refresh);
+		run.poll_rate = run.poll_rate.or(profile.poll_rate);
+		run.character = run.character.take().or(profile.character.clone());
+		run.export = run.export.take().or(profile.export.clone());
+
+		if self.log_level.is_none() {
+			if let Some(level) = &profile.log_level {
+				let level =
+					Level::from_str(level).map_err(|_| format!("invalid log level '{}'", level))?;
+				self.log_level = Some(level);
+			}
+		}
+
+		if let Some(color) = &profile.gauge_color {
+			self.colors.gauge = parse_color(color)?;
+		}
+		if let Some(color) = &profile.background_color {
+			self.colors.background = parse_color(color)?;
+		}
+		if let Some(color) = &profile.capture_color {
+			self.colors.capture = parse_color(color)?;
+		}
+
+		Ok(())
+	}
+
+	/// reads the config file again and applies it on top of the command line arguments.
+	/// the log level can't change anymore at this point, so it is kept as is
+	pub fn reload_profile(&mut self) -> Result<(), String> {
+		let profile = self.load_profile()?;
+
+		let mut conf = self.clone();
+		conf.run = self.cli_run.clone();
+		conf.colors = Colors::default();
+		conf.apply_profile(&profile)?;
+
+		*self = conf;
+		Ok(())
+	}
+}
+
+/// watches the config file for changes without blocking
+#[derive(Debug)]
+pub struct ConfigWatcher {
+	inotify: Inotify,
+	file_name: OsString,
+}
+
+impl ConfigWatcher {
+	pub fn new(path: &Path) -> io::Result<Self> {
+		let (dir, file_name) = match (path.parent(), path.file_name()) {
+			(Some(dir), Some(file_name)) => (dir, file_name.to_os_string()),
+			_ => return Err(io::Error::from(io::ErrorKind::InvalidInput)),
+		};
+
+		// a relative path without a directory is in the current one
+		let dir = match dir.as_os_str().is_empty() {
+			true => Path::new("."),
+			false => dir,
+		};
+		// the directory has to exist to be watched, and creating the first config shouldn't need a restart
+		fs::create_dir_all(dir)?;
+
+		let inotify = Inotify::init(InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC)?;
+		// editors usually replace the file instead of writing to it, so the directory is watched
+		inotify.add_watch(
+			dir,
+			AddWatchFlags::IN_CLOSE_WRITE | AddWatchFlags::IN_MOVED_TO | AddWatchFlags::IN_CREATE,
+		)?;
+
+		Ok(Self { inotify, file_name })
+	}
+
+	/// checks if the config file changed since the last call
+	pub fn changed(&self) -> bool {
+		let mut changed = false;
+		// read_events fails with EAGAIN once there are no more events
+		while let Ok(events) = self.inotify.read_events() {
+			changed |= events
+				.iter()
+				.any(|event| event.name.as_ref() == Some(&self.file_name));
+		}
+		changed
 	}
 }
 
 pub fn get_config() -> Config {
 	let mut conf = Config::parse();
 
+	// move the arguments of the subcommands into the places the rest of the app reads them from
+	match &conf.command {
+		Some(Command::Run(run)) => conf.run = run.merge(&conf.run),
+		Some(Command::Replay { path, run }) => {
+			conf.run = run.merge(&conf.run);
+			conf.load_dump = Some(path.clone());
+		}
+		Some(Command::Scan(args)) => conf.load_dump = args.dump.clone(),
+		_ => (),
+	}
+
+	conf.cli_run = conf.run.clone();
+	if let Err(e) = conf
+		.load_profile()
+		.and_then(|profile| conf.apply_profile(&profile))
+	{
+		eprintln!("Failed to load the config: {}", e);
+		std::process::exit(1);
+	}
+
 	if conf.log_file.is_none() {
 		match dirs::cache_dir() {
 			None => eprintln!("Failed to get cache dir! Will not log to file."),
@@ -79,3 +579,72 @@ pub fn get_config() -> Config {
 
 	conf
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(args: &[&str]) -> Result<Config, clap::Error> {
+		Config::try_parse_from(std::iter::once("linux-hunter-rs").chain(args.iter().copied()))
+	}
+
+	#[test]
+	fn test_merge_run_args() {
+		let conf = parse(&["-m", "--refresh", "10", "run", "-c", "--refresh", "20"]).unwrap();
+		let Some(Command::Run(run)) = &conf.command else {
+			panic!("{:?}", conf.command);
+		};
+		let run = run.merge(&conf.run);
+
+		assert!(run.show_monsters && run.show_crowns);
+		// the subcommand's own args win
+		assert_eq!(run.refresh, Some(20.0));
+	}
+
+	#[test]
+	fn test_apply_profile() {
+		let file: ConfigFile = toml::from_str(
+			r#"
+			[defaults]
+			show_monsters = true
+			show_crowns = true
+			export = "game.json"
+
+			[profiles.quiet]
+			show_monsters = false
+			"#,
+		)
+		.unwrap();
+		let profile = file.defaults.merge(file.profiles["quiet"].clone());
+
+		let mut conf = parse(&[]).unwrap();
+		conf.apply_profile(&profile).unwrap();
+		assert!(!conf.run.show_monsters);
+		assert!(conf.run.show_crowns);
+		assert_eq!(conf.run.export.as_deref(), Some("game.json"));
+
+		// the command line wins over the profile
+		let mut conf = parse(&["-m", "--export", "other.json"]).unwrap();
+		conf.apply_profile(&profile).unwrap();
+		assert!(conf.run.show_monsters);
+		assert_eq!(conf.run.export.as_deref(), Some("other.json"));
+	}
+
+	#[test]
+	fn test_config_watcher() {
+		let dir = std::env::temp_dir().join(format!("linux-hunter-conf-{}", std::process::id()));
+		let path = dir.join("config.toml");
+
+		// the directory doesn't exist yet
+		let watcher = ConfigWatcher::new(&path).unwrap();
+		assert!(!watcher.changed());
+
+		fs::write(dir.join("other.toml"), "").unwrap();
+		assert!(!watcher.changed());
+		fs::write(&path, "").unwrap();
+		let changed = watcher.changed();
+		fs::remove_dir_all(&dir).unwrap();
+
+		assert!(changed);
+	}
+}
@@ -0,0 +1,85 @@
+use crate::memory::pattern::PatternType;
+use nix::{errno::Errno, unistd::Pid};
+use std::{io, path::PathBuf};
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("Can't find MH:W pid")]
+	ProcessNotFound,
+
+	#[error(
+		"Permission denied while accessing pid {pid} (is /proc/sys/kernel/yama/ptrace_scope > 0?)"
+	)]
+	PermissionDenied { pid: Pid },
+
+	#[error("Failed to read memory at 0x{addr:X}: {source}")]
+	ReadFailed { addr: usize, source: Errno },
+
+	#[error("Read {got} bytes instead of {want}")]
+	PartialRead { got: usize, want: usize },
+
+	#[error("Can't find AoB for patterns::{0:?}")]
+	PatternNotFound(PatternType),
+
+	#[error("Monster not found: {0}")]
+	UnknownMonster(u32),
+
+	#[error("Memory region has no data")]
+	NoRegionData,
+
+	#[error("Invalid region sequence - {0}")]
+	InvalidRegions(&'static str),
+
+	#[error("Invalid pointer 0x{addr:X} read from the game")]
+	InvalidPointer { addr: usize },
+
+	#[error("Invalid dump file - {0}")]
+	InvalidDump(&'static str),
+
+	#[error("Unsupported dump version {0}, expected {1}")]
+	UnsupportedDumpVersion(u32, u32),
+
+	#[error("Refusing to write the dump to {path:?}, {reason}")]
+	UnsafeDumpTarget { path: PathBuf, reason: &'static str },
+
+	#[error("Unsupported instruction at 0x{addr:X}")]
+	UnsupportedInstruction { addr: usize },
+
+	#[error("No rip-relative operand {operand} in the instructions before 0x{addr:X}")]
+	OperandNotFound { addr: usize, operand: usize },
+
+	#[error("Invalid crown collection {path:?}: {reason}")]
+	InvalidCollection { path: PathBuf, reason: String },
+
+	#[error("Can't save crown collection {path:?}: {reason}")]
+	SaveCollection { path: PathBuf, reason: String },
+
+	#[error("not implemented")]
+	NotImplemented,
+
+	#[error(transparent)]
+	Io(#[from] io::Error),
+}
+
+impl Error {
+	// maps an errno returned by a syscall on the game process to the matching variant
+	pub fn from_errno(errno: Errno, pid: Pid, addr: usize) -> Self {
+		match errno {
+			Errno::EPERM | Errno::EACCES => Error::PermissionDenied { pid },
+			Errno::ESRCH => Error::ProcessNotFound,
+			source => Error::ReadFailed { addr, source },
+		}
+	}
+
+	// maps an io error from reading a file in /proc/<pid>/ to the matching variant
+	pub fn from_proc_io(err: io::Error, pid: Pid) -> Self {
+		match err.kind() {
+			io::ErrorKind::NotFound => Error::ProcessNotFound,
+			io::ErrorKind::PermissionDenied => Error::PermissionDenied { pid },
+			_ => Error::Io(err),
+		}
+	}
+}
@@ -0,0 +1,264 @@
+// regression tests that run the pattern search and update_all against dumps instead of the game.
+// besides the synthetic dump built here, every `tests/fixtures/<name>.lhd` (e.g. a trimmed `dump --partial`)
+// is checked against the pattern addresses in `tests/fixtures/<name>.toml`:
+//
+// [patterns]
+// PlayerNameLinux = 0x140000000
+
+use super::{
+	dump::{read_dump, DumpHeader, DumpWriter, DUMP_VERSION},
+	pattern::{all_pattern_getters, signature, PatternGetter, PatternType, SIGNATURES},
+	reader::{DumpReader, ReadPlan},
+	region::{verify_regions, MemoryRegion},
+	update::{update_all, update_consistent},
+};
+use crate::mhw::{
+	data::{BasePointers, GameData, SessionInfo},
+	offsets,
+};
+use serde::Deserialize;
+use std::{collections::HashMap, fs, path::Path};
+
+const CODE: usize = 0x140000000;
+const CODE_LEN: usize = 0x1000;
+const CODE_2: usize = 0x140002000;
+const CODE_2_LEN: usize = 0x100;
+// the globals the code patterns load their pointers from
+const GLOBALS: usize = 0x141000000;
+// the structures those pointers lead to
+const SESSION: usize = 0x150000000;
+const PLAYERS: usize = 0x160000000;
+const MONSTERS: usize = 0x170000000;
+
+#[derive(Default)]
+struct Fixture {
+	regions: Vec<MemoryRegion>,
+}
+
+impl Fixture {
+	fn region(&mut self, begin: usize, len: usize) {
+		self.regions
+			.push(MemoryRegion::from_vec(begin, vec![0; len], "r-xp", "", ""));
+	}
+
+	// writes into the region containing addr, or a new page if there is none
+	fn write(&mut self, addr: usize, bytes: &[u8]) {
+		let region = match self
+			.regions
+			.iter_mut()
+			.find(|r| r.get_begin() <= addr && addr + bytes.len() <= r.get_end())
+		{
+			Some(region) => region,
+			None => {
+				self.regions.push(MemoryRegion::from_vec(
+					addr & !0xFFF,
+					vec![0; 0x1000],
+					"rw-p",
+					"",
+					"",
+				));
+				self.regions.last_mut().unwrap()
+			}
+		};
+
+		let start = addr - region.get_begin();
+		region.data.as_mut().unwrap()[start..start + bytes.len()].copy_from_slice(bytes);
+	}
+
+	// goes through the dump file format, so the fixture is loaded like a real dump
+	fn into_dump(mut self) -> Vec<MemoryRegion> {
+		self.regions.sort_by_key(MemoryRegion::get_begin);
+
+		let header = DumpHeader {
+			version: DUMP_VERSION,
+			game_build: None,
+			pid: 0,
+			timestamp: 0,
+			maps: String::new(),
+			partial: true,
+		};
+		let mut writer = DumpWriter::new(Vec::new(), &header).unwrap();
+		for region in &self.regions {
+			writer.write_region(region).unwrap();
+		}
+		let file = writer.finish().unwrap();
+
+		read_dump(&file[..]).unwrap().1
+	}
+}
+
+// the same search the binary does
+fn search(regions: &[MemoryRegion]) -> [PatternGetter; 8] {
+	verify_regions(regions).unwrap();
+
+	let mut getters = all_pattern_getters();
+	for getter in &mut getters {
+		for region in regions {
+			let _ = getter.search(region);
+		}
+		getter.apply_rules(regions);
+	}
+
+	getters
+}
+
+fn address_of(getters: &[PatternGetter], pattern_type: PatternType) -> Option<usize> {
+	getters
+		.iter()
+		.find(|g| g.pattern_type == pattern_type)
+		.and_then(|g| g.mem_location)
+		.map(|loc| loc.address)
+}
+
+fn synthetic() -> (Vec<MemoryRegion>, Vec<(PatternType, usize)>) {
+	let mut fixture = Fixture::default();
+	fixture.region(CODE, CODE_LEN);
+	fixture.region(CODE_2, CODE_2_LEN);
+
+	let address = |pattern_type| match pattern_type {
+		PatternType::PlayerName => CODE + 0x100,
+		PatternType::CurrentPlayerName => CODE + 0x200,
+		PatternType::PlayerDamage => CODE + 0x300,
+		PatternType::Monsters => CODE + 0x400,
+		PatternType::PlayerBuff => CODE + 0x600,
+		PatternType::LobbyStatus => CODE + 0x700,
+		// right at the end of a region
+		PatternType::Emetta => CODE + CODE_LEN - 6,
+		PatternType::PlayerNameLinux => CODE_2 + CODE_2_LEN - 36,
+	};
+	let patterns: Vec<_> = SIGNATURES
+		.iter()
+		.map(|(pattern_type, sig)| (*pattern_type, address(*pattern_type), *sig))
+		.collect();
+
+	for (_, addr, sig) in &patterns {
+		fixture.write(*addr, &signature(sig, 0x11));
+	}
+	// a false start of the player name signature, right in front of the real one
+	fixture.write(CODE + 0x100 - 7, &signature("48 8B 0D ?? ?? ?? ??", 0x22));
+
+	// the rip-relative operands of the patterns update_all follows, each leading to a global with the base pointer
+	let bases = [
+		(PatternType::LobbyStatus, SESSION),
+		(PatternType::PlayerDamage, PLAYERS),
+		(PatternType::Monsters, MONSTERS),
+	];
+	for (i, (pattern_type, base)) in bases.into_iter().enumerate() {
+		let instruction = address(pattern_type);
+		let global = GLOBALS + i * 8;
+		let displacement = (global - (instruction + 7)) as i32;
+		fixture.write(instruction + 3, &displacement.to_le_bytes());
+		fixture.write(global, &(base as u64).to_le_bytes());
+	}
+
+	fixture.write(SESSION + offsets::SESSION_ID, b"SESSIONID012");
+	fixture.write(SESSION + offsets::SESSION_HOST_NAME, b"Hunter");
+	fixture.write(SESSION + offsets::MISSION_STATUS_OFFSET, &[1]);
+
+	let expected = patterns.iter().map(|(t, addr, _)| (*t, *addr)).collect();
+	(fixture.into_dump(), expected)
+}
+
+#[test]
+fn test_synthetic_patterns() {
+	let (regions, expected) = synthetic();
+	let getters = search(&regions);
+
+	for (pattern_type, addr) in expected {
+		assert_eq!(
+			address_of(&getters, pattern_type),
+			Some(addr),
+			"{:?}",
+			pattern_type
+		);
+	}
+}
+
+fn synthetic_data() -> GameData {
+	let mut hostname = String::from("Hunter");
+	hostname.extend(std::iter::repeat_n('\0', offsets::PLAYER_NAME_LENGTH - 6));
+
+	// damage and monsters aren't implemented yet, so they stay empty
+	GameData {
+		pointers: BasePointers {
+			players: Some(PLAYERS),
+			monsters: Some(MONSTERS),
+		},
+		..GameData::new(SessionInfo {
+			session_id: Box::from("SESSIONID012"),
+			hostname: hostname.into_boxed_str(),
+			is_mission: true,
+			is_expedition: false,
+		})
+	}
+}
+
+#[test]
+fn test_synthetic_update_all() {
+	let (regions, _) = synthetic();
+	let getters = search(&regions);
+	let reader = DumpReader::new(regions);
+
+	assert_eq!(
+		update_all(&reader, &getters, true).unwrap(),
+		synthetic_data()
+	);
+}
+
+#[test]
+fn test_synthetic_update_consistent() {
+	let (regions, _) = synthetic();
+	let getters = search(&regions);
+	let reader = DumpReader::new(regions);
+
+	// the first attempt has nothing to prefetch yet, so it reads field by field
+	let mut first = ReadPlan::default();
+	first
+		.run(&reader, |reader| update_all(reader, &getters, true))
+		.unwrap();
+	assert!(!first.is_complete());
+
+	// and the second one reads everything at once
+	let mut plan = ReadPlan::default();
+	let data = update_consistent(&reader, &mut plan, &getters, true, &GameData::default());
+	assert_eq!(data.unwrap(), synthetic_data());
+	assert!(plan.is_complete());
+}
+
+#[derive(Deserialize)]
+struct Expected {
+	patterns: HashMap<String, usize>,
+}
+
+#[test]
+fn test_fixture_dumps() {
+	let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+	let mut checked = 0;
+
+	for entry in fs::read_dir(&dir).unwrap() {
+		let path = entry.unwrap().path();
+		if path.extension().is_none_or(|ext| ext != "lhd") {
+			continue;
+		}
+
+		let expected: Expected =
+			toml::from_str(&fs::read_to_string(path.with_extension("toml")).unwrap()).unwrap();
+		let regions = super::dump::load(path.to_str().unwrap()).unwrap();
+		let getters = search(&regions);
+
+		for getter in &getters {
+			let name = format!("{:?}", getter.pattern_type);
+			assert_eq!(
+				getter.mem_location.map(|loc| loc.address),
+				expected.patterns.get(&name).copied(),
+				"{} in {}",
+				name,
+				path.display()
+			);
+		}
+		checked += 1;
+	}
+
+	// a missing or emptied directory would otherwise pass without checking anything
+	assert!(checked > 0, "no fixtures in {}", dir.display());
+}
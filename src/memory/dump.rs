@@ -0,0 +1,516 @@
+use super::{
+	pattern::{PatternGetter, PatternType},
+	reader::{coalesce, MemoryReader, RecordingReader},
+	region::MemoryRegion,
+	update::update_all,
+};
+use crate::{
+	error::{Error, Result},
+	mhw::process::game_build,
+};
+use nix::unistd::Pid;
+use serde::{Deserialize, Serialize};
+use std::{
+	fs::{self, File, OpenOptions},
+	io::{self, BufReader, BufWriter, Read, Write},
+	ops::Range,
+	path::{Path, PathBuf},
+	time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::debug;
+
+// layout of a dump file:
+// MAGIC | header len (u32 le) | header json | { region len (u32 le) | region json | zstd payload }*
+const MAGIC: &[u8; 8] = b"LHRSDUMP";
+pub const DUMP_VERSION: u32 = 1;
+pub const DUMP_EXTENSION: &str = "lhd";
+const COMPRESSION_LEVEL: i32 = 3;
+
+// patterns that point to the structures update_all reads
+const CHAIN_PATTERNS: [PatternType; 3] = [
+	PatternType::LobbyStatus,
+	PatternType::PlayerDamage,
+	PatternType::Monsters,
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DumpHeader {
+	pub version: u32,
+	// the steam build id, if it could be found
+	pub game_build: Option<String>,
+	pub pid: i32,
+	// seconds since the unix epoch
+	pub timestamp: u64,
+	// contents of /proc/<pid>/maps at the time of the dump
+	pub maps: String,
+	// only parts of the memory were dumped
+	#[serde(default)]
+	pub partial: bool,
+}
+
+impl DumpHeader {
+	pub fn new(pid: Pid) -> Result<Self> {
+		let maps = fs::read_to_string(format!("/proc/{}/maps", pid))
+			.map_err(|e| Error::from_proc_io(e, pid))?;
+
+		Ok(Self {
+			version: DUMP_VERSION,
+			game_build: game_build(&maps),
+			pid: pid.as_raw(),
+			timestamp: SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.map(|t| t.as_secs())
+				.unwrap_or(0),
+			maps,
+			partial: false,
+		})
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RegionHeader {
+	begin: usize,
+	end: usize,
+	permissions: String,
+	path: String,
+	compressed_len: u64,
+}
+
+fn write_json<W: Write, T: Serialize>(out: &mut W, value: &T) -> Result<()> {
+	let json =
+		serde_json::to_vec(value).map_err(|_| Error::InvalidDump("can't serialize header"))?;
+	out.write_all(&(json.len() as u32).to_le_bytes())?;
+	out.write_all(&json)?;
+	Ok(())
+}
+
+// lengths come from the file, so they can't be trusted to allocate a buffer up front
+fn read_len<R: Read>(input: &mut R, len: u64) -> Result<Vec<u8>> {
+	let mut buf = Vec::new();
+	input.take(len).read_to_end(&mut buf)?;
+	Ok(buf)
+}
+
+// returns None on a clean end of file
+fn read_json<R: Read, T: for<'de> Deserialize<'de>>(input: &mut R) -> Result<Option<T>> {
+	let len = read_len(input, 4)?;
+	let len = match len.len() {
+		0 => return Ok(None),
+		4 => u32::from_le_bytes(len[..].try_into().unwrap()),
+		_ => return Err(Error::InvalidDump("truncated header")),
+	};
+
+	let json = read_len(input, len as u64)?;
+	if json.len() != len as usize {
+		return Err(Error::InvalidDump("truncated header"));
+	}
+
+	serde_json::from_slice(&json)
+		.map(Some)
+		.map_err(|_| Error::InvalidDump("corrupt header"))
+}
+
+/// writes memory regions into a single compressed dump file, one region at a time
+pub struct DumpWriter<W: Write> {
+	out: W,
+	pub regions: usize,
+}
+
+impl DumpWriter<BufWriter<File>> {
+	/// creates a new file at `path`, fails instead of truncating anything that is already there
+	pub fn create(path: &Path, header: &DumpHeader) -> Result<Self> {
+		let file = match OpenOptions::new().write(true).create_new(true).open(path) {
+			Ok(file) => file,
+			Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+				return Err(Error::UnsafeDumpTarget {
+					path: path.to_path_buf(),
+					reason: "it already exists",
+				});
+			}
+			Err(e) => return Err(e.into()),
+		};
+		Self::new(BufWriter::new(file), header)
+	}
+}
+
+impl<W: Write> DumpWriter<W> {
+	pub fn new(mut out: W, header: &DumpHeader) -> Result<Self> {
+		out.write_all(MAGIC)?;
+		write_json(&mut out, header)?;
+		Ok(Self { out, regions: 0 })
+	}
+
+	pub fn write_region(&mut self, region: &MemoryRegion) -> Result<()> {
+		let data = region.data.as_ref().ok_or(Error::NoRegionData)?;
+		let compressed = zstd::encode_all(&data[..], COMPRESSION_LEVEL)?;
+
+		let header = RegionHeader {
+			begin: region.get_begin(),
+			end: region.get_begin() + data.len(),
+			permissions: region.permissions.to_string(),
+			path: region.path.to_string(),
+			compressed_len: compressed.len() as u64,
+		};
+		write_json(&mut self.out, &header)?;
+		self.out.write_all(&compressed)?;
+
+		self.regions += 1;
+		Ok(())
+	}
+
+	pub fn finish(mut self) -> Result<W> {
+		self.out.flush()?;
+		Ok(self.out)
+	}
+}
+
+fn read_header<R: Read>(input: &mut R) -> Result<DumpHeader> {
+	let mut magic = [0u8; MAGIC.len()];
+	input
+		.read_exact(&mut magic)
+		.map_err(|_| Error::InvalidDump("not a dump file"))?;
+	if magic != *MAGIC {
+		return Err(Error::InvalidDump("not a dump file"));
+	}
+
+	let header: DumpHeader = read_json(input)?.ok_or(Error::InvalidDump("missing header"))?;
+	if header.version != DUMP_VERSION {
+		return Err(Error::UnsupportedDumpVersion(header.version, DUMP_VERSION));
+	}
+
+	Ok(header)
+}
+
+/// reads a dump file, the regions keep the addresses they had in the game
+pub fn read_dump<R: Read>(mut input: R) -> Result<(DumpHeader, Vec<MemoryRegion>)> {
+	let header = read_header(&mut input)?;
+
+	let mut regions = Vec::new();
+	while let Some(region) = read_json::<_, RegionHeader>(&mut input)? {
+		let compressed = read_len(&mut input, region.compressed_len)?;
+		if compressed.len() as u64 != region.compressed_len {
+			return Err(Error::InvalidDump("truncated region"));
+		}
+
+		let size = region
+			.end
+			.checked_sub(region.begin)
+			.ok_or(Error::InvalidDump("region ends before it begins"))?;
+
+		// never decompress more than the region claims to be
+		let mut data = Vec::new();
+		zstd::Decoder::new(&compressed[..])?
+			.take(size as u64 + 1)
+			.read_to_end(&mut data)?;
+		if data.len() != size {
+			return Err(Error::InvalidDump("region size doesn't match its data"));
+		}
+
+		let debug_info = format!(
+			"{:x}-{:x} {} {}",
+			region.begin, region.end, region.permissions, region.path
+		);
+		regions.push(MemoryRegion::from_vec(
+			region.begin,
+			data,
+			&region.permissions,
+			&region.path,
+			&debug_info,
+		));
+	}
+
+	debug!("loaded {} regions from dump", regions.len());
+
+	Ok((header, regions))
+}
+
+/// only reads the header of a dump file, without decompressing any memory
+pub fn read_dump_header(path: &Path) -> Result<DumpHeader> {
+	read_header(&mut BufReader::new(File::open(path)?))
+}
+
+/// collects the memory a partial dump needs, so `update_all` can run against it: a window around every found pattern,
+/// `chain_len` bytes at the structures the pointer patterns lead to and everything `update_all` reads itself
+pub fn partial_ranges<R: MemoryReader + ?Sized>(
+	reader: &R,
+	patterns: &[PatternGetter],
+	window: usize,
+	chain_len: usize,
+) -> Vec<Range<usize>> {
+	let mut ranges = Vec::new();
+
+	for pattern in patterns {
+		let Some(loc) = pattern.mem_location else {
+			continue;
+		};
+		ranges.push(loc.address.saturating_sub(window)..loc.address.saturating_add(window));
+
+		if !CHAIN_PATTERNS.contains(&pattern.pattern_type) {
+			continue;
+		}
+
+		let Ok(target) = pattern.resolve(reader) else {
+			continue;
+		};
+		ranges.push(target..target.saturating_add(chain_len));
+
+		// the target is a pointer to the actual structure
+		if let Ok(mem) = reader.read(target, size_of::<u64>()) {
+			let pointer = u64::from_le_bytes(mem[..].try_into().unwrap()) as usize;
+			if pointer != 0 {
+				ranges.push(pointer..pointer.saturating_add(chain_len));
+			}
+		}
+	}
+
+	let recorder = RecordingReader::new(reader);
+	if let Err(e) = update_all(&recorder, patterns, true) {
+		debug!(
+			"update_all failed while collecting memory for a partial dump: {}",
+			e
+		);
+	}
+	ranges.extend(recorder.into_ranges());
+
+	coalesce(ranges)
+}
+
+/// cuts the ranges down to the parts that are inside of the regions, since the rest can't be read anyways
+pub fn clip_to_regions(ranges: &[Range<usize>], regions: &[MemoryRegion]) -> Vec<MemoryRegion> {
+	let mut res = Vec::new();
+
+	for range in ranges {
+		for region in regions {
+			let begin = range.start.max(region.get_begin());
+			let end = range.end.min(region.get_end());
+			if begin >= end {
+				continue;
+			}
+
+			let debug_info = format!(
+				"{:x}-{:x} {} {} (part of {})",
+				begin, end, region.permissions, region.path, region.debug_info
+			);
+			res.push(MemoryRegion::new(
+				begin,
+				end,
+				&region.permissions,
+				&region.path,
+				&debug_info,
+			));
+		}
+	}
+
+	res.sort_by_key(MemoryRegion::get_begin);
+	res
+}
+
+/// checks if a file starts like a dump file, regardless of its version
+pub fn is_dump_file(path: &Path) -> bool {
+	let mut magic = [0u8; MAGIC.len()];
+	File::open(path).is_ok_and(|mut file| file.read_exact(&mut magic).is_ok() && magic == *MAGIC)
+}
+
+/// decides where a dump gets written to, nothing that isn't a dump is ever replaced.
+/// a directory gets a new timestamped dump file, an existing dump file is only replaced with `overwrite`
+pub fn dump_target(path: &Path, overwrite: bool, timestamp: u64) -> Result<PathBuf> {
+	let unsafe_target = |path: &Path, reason| Error::UnsafeDumpTarget {
+		path: path.to_path_buf(),
+		reason,
+	};
+
+	if path.is_dir() {
+		let file = path.join(format!("mhw-{}.{}", timestamp, DUMP_EXTENSION));
+		if file.exists() {
+			return Err(unsafe_target(&file, "it already exists"));
+		}
+		return Ok(file);
+	}
+
+	if !path.exists() {
+		return Ok(path.to_path_buf());
+	}
+
+	if !is_dump_file(path) {
+		return Err(unsafe_target(path, "it exists and is not a dump"));
+	}
+	if !overwrite {
+		return Err(unsafe_target(
+			path,
+			"it already exists, use --overwrite to replace it",
+		));
+	}
+
+	Ok(path.to_path_buf())
+}
+
+/// moves the finished dump from `tmp` to the `target` of `dump_target`. only an existing dump is replaced,
+/// and only with `overwrite`, anything that appeared at `target` in the meantime is left alone
+pub fn persist_dump(tmp: &Path, target: &Path, overwrite: bool) -> Result<()> {
+	if overwrite && is_dump_file(target) {
+		fs::rename(tmp, target)?;
+		return Ok(());
+	}
+
+	// unlike rename, linking fails if the target exists
+	match fs::hard_link(tmp, target) {
+		Ok(()) => Ok(fs::remove_file(tmp)?),
+		Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Err(Error::UnsafeDumpTarget {
+			path: target.to_path_buf(),
+			reason: "it was created while dumping",
+		}),
+		Err(e) => Err(e.into()),
+	}
+}
+
+// dumps from older versions are a directory with a `<begin>.bin` file per region
+fn load_dir(path: &Path) -> Result<Vec<MemoryRegion>> {
+	let mut res = Vec::new();
+
+	for entry in fs::read_dir(path)? {
+		let path = entry?.path();
+
+		let begin = match path
+			.file_stem()
+			.and_then(|stem| usize::from_str_radix(stem.to_str()?, 16).ok())
+		{
+			Some(begin) => begin,
+			None => continue,
+		};
+
+		let data = fs::read(&path)?;
+		res.push(MemoryRegion::from_vec(
+			begin,
+			data,
+			"r---",
+			"",
+			&path.to_string_lossy(),
+		));
+	}
+
+	res.sort_by_key(MemoryRegion::get_begin);
+
+	Ok(res)
+}
+
+/// loads a dump file, or a directory dumped by an older version
+pub fn load(path: &str) -> Result<Vec<MemoryRegion>> {
+	let path = Path::new(path);
+	if path.is_dir() {
+		return load_dir(path);
+	}
+
+	let (_, regions) = read_dump(BufReader::new(File::open(path)?))?;
+	Ok(regions)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_dump_roundtrip() {
+		let header = DumpHeader {
+			version: DUMP_VERSION,
+			game_build: Some(String::from("12345")),
+			pid: 42,
+			timestamp: 1700000000,
+			maps: String::from("140000000-140001000 r-xp 00000000 00:00 0\n"),
+			partial: false,
+		};
+
+		let first = MemoryRegion::from_vec(0x140000000, vec![0xAB; 0x1000], "r-xp", "", "");
+		let second =
+			MemoryRegion::from_vec(0x7f0000000000, (0..=255).collect(), "rw-p", "[heap]", "");
+
+		let mut writer = DumpWriter::new(Vec::new(), &header).unwrap();
+		writer.write_region(&first).unwrap();
+		writer.write_region(&second).unwrap();
+		let file = writer.finish().unwrap();
+
+		let (read_header, regions) = read_dump(&file[..]).unwrap();
+		assert_eq!(read_header.pid, 42);
+		assert_eq!(read_header.game_build.as_deref(), Some("12345"));
+		assert_eq!(read_header.maps, header.maps);
+
+		assert_eq!(regions.len(), 2);
+		assert_eq!(regions[0].get_begin(), 0x140000000);
+		assert_eq!(regions[0].get_end(), 0x140001000);
+		assert_eq!(regions[1].get_begin(), 0x7f0000000000);
+		assert_eq!(&*regions[1].permissions, "rw-p");
+		assert_eq!(&*regions[1].path, "[heap]");
+		assert_eq!(regions[1].data, second.data);
+
+		assert!(matches!(
+			read_dump(&file[..file.len() - 1]),
+			Err(Error::InvalidDump(_))
+		));
+		assert!(matches!(
+			read_dump(&b"not a dump"[..]),
+			Err(Error::InvalidDump(_))
+		));
+
+		// a corrupt length must not allocate gigabytes before failing
+		let mut huge = MAGIC.to_vec();
+		huge.extend_from_slice(&u32::MAX.to_le_bytes());
+		assert!(matches!(read_dump(&huge[..]), Err(Error::InvalidDump(_))));
+	}
+
+	#[test]
+	fn test_clip_to_regions() {
+		let regions = [
+			MemoryRegion::new(0x1000, 0x2000, "r--p", "", ""),
+			MemoryRegion::new(0x3000, 0x4000, "rw-p", "[heap]", ""),
+		];
+
+		let clipped = clip_to_regions(&[0x0800..0x1100, 0x1F00..0x3100, 0x5000..0x6000], &regions);
+		let bounds: Vec<_> = clipped
+			.iter()
+			.map(|r| (r.get_begin(), r.get_end()))
+			.collect();
+		assert_eq!(
+			bounds,
+			vec![(0x1000, 0x1100), (0x1F00, 0x2000), (0x3000, 0x3100)]
+		);
+		assert_eq!(&*clipped[2].path, "[heap]");
+	}
+
+	#[test]
+	fn test_dump_target() {
+		let dir = std::env::temp_dir().join(format!("linux-hunter-rs-test-{}", std::process::id()));
+		fs::create_dir_all(&dir).unwrap();
+
+		let new = dir.join("new.lhd");
+		let other = dir.join("notes.txt");
+		let dump = dir.join("old.lhd");
+		fs::write(&other, "important").unwrap();
+		fs::write(&dump, MAGIC).unwrap();
+
+		assert_eq!(
+			dump_target(&dir, false, 123).unwrap(),
+			dir.join("mhw-123.lhd")
+		);
+		assert_eq!(dump_target(&new, false, 123).unwrap(), new);
+		assert!(dump_target(&other, true, 123).is_err());
+		assert!(dump_target(&dump, false, 123).is_err());
+		assert_eq!(dump_target(&dump, true, 123).unwrap(), dump);
+
+		// the staging file is never truncated
+		let header = DumpHeader::new(Pid::from_raw(std::process::id() as i32)).unwrap();
+		assert!(matches!(
+			DumpWriter::create(&other, &header),
+			Err(Error::UnsafeDumpTarget { .. })
+		));
+		assert_eq!(fs::read(&other).unwrap(), b"important");
+
+		// nothing but a dump is replaced, even if it appeared after dump_target
+		let tmp = dir.join("new.lhd.tmp");
+		DumpWriter::create(&tmp, &header).unwrap().finish().unwrap();
+		assert!(persist_dump(&tmp, &other, true).is_err());
+		assert_eq!(fs::read(&other).unwrap(), b"important");
+		persist_dump(&tmp, &new, false).unwrap();
+		assert!(is_dump_file(&new) && !tmp.exists());
+
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
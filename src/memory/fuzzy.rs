@@ -0,0 +1,303 @@
+// finds the places a signature most likely moved to after a game update broke it.
+// a candidate may have some bytes that differ from the signature, and the wildcard groups may have grown or shrunk,
+// e.g. when an immediate operand went from 8 to 32 bits
+
+use super::{pattern::parse_signature, region::MemoryRegion};
+use std::collections::HashMap;
+
+// the signature is split into chunks of this size that have to match exactly to find candidates
+const ANCHOR_LEN: usize = 3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct FuzzyOptions {
+	// how many of the known bytes may differ
+	pub max_mismatches: usize,
+	// by how many bytes each wildcard group may grow or shrink
+	pub max_shift: usize,
+}
+
+impl Default for FuzzyOptions {
+	fn default() -> Self {
+		Self {
+			max_mismatches: 4,
+			max_shift: 4,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+	pub address: usize,
+	pub mismatches: usize,
+	// how many wildcard groups changed their length
+	pub shifts: usize,
+	// 1.0 for an exact match, every mismatch and shift lowers it
+	pub confidence: f32,
+	// the signature matching the candidate, with the bytes that differ turned into wildcards
+	pub signature: String,
+}
+
+// known bytes and the amount of wildcards in front of them
+#[derive(Debug)]
+struct Section {
+	gap: usize,
+	bytes: Vec<u8>,
+}
+
+fn sections(sig: &[Option<u8>]) -> Vec<Section> {
+	let mut res: Vec<Section> = Vec::new();
+	let mut gap = 0;
+
+	for (i, byte) in sig.iter().enumerate() {
+		match byte {
+			None => gap += 1,
+			Some(byte) if gap == 0 && i > 0 => res.last_mut().unwrap().bytes.push(*byte),
+			Some(byte) => {
+				res.push(Section {
+					gap,
+					bytes: vec![*byte],
+				});
+				gap = 0;
+			}
+		}
+	}
+
+	// the gap of trailing wildcards is dropped, they don't change where a signature matches
+	res
+}
+
+// where each section was placed and which of its bytes differ
+struct Placement {
+	pos: usize,
+	differs: Vec<bool>,
+}
+
+fn place(section: &Section, data: &[u8], pos: usize) -> Option<Placement> {
+	let found = data.get(pos..pos.checked_add(section.bytes.len())?)?;
+	Some(Placement {
+		pos,
+		differs: found
+			.iter()
+			.zip(&section.bytes)
+			.map(|(a, b)| a != b)
+			.collect(),
+	})
+}
+
+fn mismatches(placement: &Placement) -> usize {
+	placement.differs.iter().filter(|d| **d).count()
+}
+
+// the best placement of a section with its gap shifted by up to max_shift, positions are computed by `at`
+fn best_placement(
+	section: &Section,
+	gap: usize,
+	data: &[u8],
+	max_shift: usize,
+	at: impl Fn(usize) -> Option<usize>,
+) -> Option<(Placement, bool)> {
+	// an empty gap can't shrink and sections without a gap are one section
+	let max_shift = if gap == 0 { 0 } else { max_shift };
+
+	// no section can be placed further away than the data is long
+	let max_gap = gap.saturating_add(max_shift).min(data.len().max(gap));
+	(gap.saturating_sub(max_shift).max(1.min(gap))..=max_gap)
+		.filter_map(|new_gap| Some((place(section, data, at(new_gap)?)?, new_gap != gap)))
+		.min_by_key(|(placement, shifted)| (mismatches(placement), *shifted))
+}
+
+fn try_anchor(
+	sections: &[Section],
+	anchor: usize,
+	pos: usize,
+	data: &[u8],
+	opts: &FuzzyOptions,
+) -> Option<(usize, Vec<Placement>, Vec<usize>, usize)> {
+	let mut placements = Vec::with_capacity(sections.len());
+	placements.push(place(&sections[anchor], data, pos)?);
+	let mut shifts = 0;
+
+	// to the left of the anchor, the gap in front of the next section decides where a section ends
+	for i in (0..anchor).rev() {
+		let next = placements.last().unwrap().pos;
+		let len = sections[i].bytes.len();
+		let (placement, shifted) = best_placement(
+			&sections[i],
+			sections[i + 1].gap,
+			data,
+			opts.max_shift,
+			|gap| next.checked_sub(gap.checked_add(len)?),
+		)?;
+		shifts += shifted as usize;
+		placements.push(placement);
+	}
+	placements.reverse();
+
+	for section in &sections[anchor + 1..] {
+		let last = placements.last().unwrap();
+		let end = last.pos + last.differs.len();
+		let (placement, shifted) =
+			best_placement(section, section.gap, data, opts.max_shift, |gap| {
+				end.checked_add(gap)
+			})?;
+		shifts += shifted as usize;
+		placements.push(placement);
+	}
+
+	let total: usize = placements.iter().map(mismatches).sum();
+	if total > opts.max_mismatches {
+		return None;
+	}
+
+	let start = placements[0].pos.checked_sub(sections[0].gap)?;
+	let mut gaps = vec![sections[0].gap];
+	gaps.extend(
+		placements
+			.windows(2)
+			.map(|w| w[1].pos - (w[0].pos + w[0].differs.len())),
+	);
+
+	Some((start, placements, gaps, shifts))
+}
+
+fn suggest(placements: &[Placement], gaps: &[usize], data: &[u8]) -> String {
+	let mut res = Vec::new();
+	for (placement, gap) in placements.iter().zip(gaps) {
+		res.extend(std::iter::repeat_n(String::from("??"), *gap));
+		for (i, differs) in placement.differs.iter().enumerate() {
+			res.push(match differs {
+				true => String::from("??"),
+				false => format!("{:02X}", data[placement.pos + i]),
+			});
+		}
+	}
+
+	// bytes that differ at the end don't help finding the signature
+	while res.last().is_some_and(|byte| byte == "??") {
+		res.pop();
+	}
+
+	res.join(" ")
+}
+
+/// every place in the region the signature matches with at most `opts.max_mismatches` differing bytes.
+/// candidates are found through parts of the signature that still match exactly,
+/// so a candidate with more mismatches than the signature has 3 byte chunks may be missed
+pub fn fuzzy_search(sig: &str, region: &MemoryRegion, opts: &FuzzyOptions) -> Vec<Candidate> {
+	let (Some(sig), Some(data)) = (parse_signature(sig), &region.data) else {
+		return Vec::new();
+	};
+	let sections = sections(&sig);
+	let known: usize = sections.iter().map(|s| s.bytes.len()).sum();
+
+	let mut found: HashMap<usize, Candidate> = HashMap::new();
+	for (anchor, section) in sections.iter().enumerate() {
+		for (offset, chunk) in section
+			.bytes
+			.chunks(ANCHOR_LEN)
+			.enumerate()
+			.map(|(i, chunk)| (i * ANCHOR_LEN, chunk))
+			.filter(|(_, chunk)| chunk.len() > 1 || section.bytes.len() == 1)
+		{
+			for pos in memchr::memmem::find_iter(data, chunk) {
+				let Some((start, placements, gaps, shifts)) = pos
+					.checked_sub(offset)
+					.and_then(|pos| try_anchor(&sections, anchor, pos, data, opts))
+				else {
+					continue;
+				};
+
+				let mismatches = placements.iter().map(mismatches).sum();
+				let candidate = Candidate {
+					address: region.get_begin() + start,
+					mismatches,
+					shifts,
+					confidence: (1.0 - (mismatches + shifts) as f32 / known as f32).clamp(0.0, 1.0),
+					signature: suggest(&placements, &gaps, data),
+				};
+
+				match found.get(&start) {
+					Some(prev) if prev.confidence >= candidate.confidence => (),
+					_ => {
+						found.insert(start, candidate);
+					}
+				}
+			}
+		}
+	}
+
+	let mut res: Vec<Candidate> = found.into_values().collect();
+	res.sort_by(|a, b| {
+		b.confidence
+			.total_cmp(&a.confidence)
+			.then(a.address.cmp(&b.address))
+	});
+
+	res
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::memory::pattern::{signature, PatternType};
+
+	const SIG: &str = "48 8B 0D ?? ?? ?? ?? B2 01 E8 ?? ?? ?? ?? C6 83 ?? ?? ?? ?? ?? 48 8B 0D";
+
+	fn region(at: usize, bytes: &[u8]) -> MemoryRegion {
+		let mut data = vec![0xCC; 0x100];
+		data[at..at + bytes.len()].copy_from_slice(bytes);
+		MemoryRegion::from_vec(0x1000, data, "r-xp", "", "")
+	}
+
+	#[test]
+	fn test_exact() {
+		let region = region(0x20, &signature(SIG, 0x11));
+		let res = fuzzy_search(SIG, &region, &FuzzyOptions::default());
+
+		assert_eq!(res[0].address, 0x1020);
+		assert_eq!(res[0].confidence, 1.0);
+		assert_eq!(res[0].signature, SIG);
+	}
+
+	#[test]
+	fn test_patched() {
+		// one byte changed and the last wildcard group grew by 3 bytes
+		let mut bytes = signature(SIG, 0x11);
+		bytes[8] = 0x02;
+		bytes.splice(16..16, [0x11; 3]);
+		let region = region(0x20, &bytes);
+
+		let res = fuzzy_search(SIG, &region, &FuzzyOptions::default());
+		assert_eq!(res[0].address, 0x1020);
+		assert_eq!((res[0].mismatches, res[0].shifts), (1, 1));
+		assert_eq!(
+			res[0].signature,
+			"48 8B 0D ?? ?? ?? ?? B2 ?? E8 ?? ?? ?? ?? C6 83 ?? ?? ?? ?? ?? ?? ?? ?? 48 8B 0D"
+		);
+		assert!(res[0].confidence < 1.0);
+
+		let strict = FuzzyOptions {
+			max_mismatches: 0,
+			max_shift: 0,
+		};
+		assert!(fuzzy_search(SIG, &region, &strict).is_empty());
+
+		let loose = FuzzyOptions {
+			max_mismatches: usize::MAX,
+			max_shift: usize::MAX,
+		};
+		let res = fuzzy_search(SIG, &region, &loose);
+		assert_eq!(res[0].address, 0x1020);
+		assert!(res.iter().all(|c| (0.0..=1.0).contains(&c.confidence)));
+	}
+
+	#[test]
+	fn test_string() {
+		let sig = PatternType::Emetta.signature();
+		let region = region(0xFA, b"Emetca");
+
+		let res = fuzzy_search(sig, &region, &FuzzyOptions::default());
+		assert_eq!(res[0].address, 0x10FA);
+		assert_eq!(res[0].signature, "45 6D 65 74 ?? 61");
+	}
+}
@@ -1,67 +1,120 @@
+#[cfg(test)]
+mod corpus;
+pub mod dump;
+pub mod fuzzy;
 pub mod pattern;
+pub mod preflight;
+pub mod reader;
 pub mod region;
 pub mod update;
+pub mod x86;
 
+use crate::error::{Error, Result};
 use nix::unistd::Pid;
 use region::MemoryRegion;
 use sscanf::scanf;
 use std::fs;
 use tracing::debug;
 
-pub fn get_memory_regions(pid: Pid, dump_loc: Option<&str>) -> anyhow::Result<Vec<MemoryRegion>> {
+pub fn get_memory_regions(pid: Pid, dump_loc: Option<&str>) -> Result<Vec<MemoryRegion>> {
 	// dont load the games memory if we are supposed to load from a dump
 	// usefull for debugging
 	if let Some(path) = dump_loc {
-		return load_dump(path);
+		return dump::load(path);
 	}
 
 	let maps_path = String::from("/proc/") + pid.to_string().as_str() + "/maps";
-	let maps = fs::read_to_string(&maps_path)?;
-
-	let mut regions: Vec<MemoryRegion> = Vec::new();
+	let maps = fs::read_to_string(&maps_path).map_err(|e| Error::from_proc_io(e, pid))?;
 
 	debug!("lines: {}", maps.lines().count());
 
-	for line in maps.lines() {
-		match scanf!(
-			line,
-			"{usize:x}-{usize:x} {&str} {usize:x} {&str} {isize}{&str}"
-		) {
-			Err(_) => continue,
-			Ok((begin, end, permissions, _offset, _device, inode, _path)) => {
-				if inode != 0 || !permissions.starts_with("r") {
-					continue;
-				}
-
-				let reg = MemoryRegion::new(begin, end, &format!("{:x}", begin), line);
-				regions.push(reg);
-			}
-		};
-	}
+	let regions = anonymous_regions(&maps);
 
 	debug!("regions: {}", regions.len());
 
 	Ok(regions)
 }
 
-fn load_dump(path: &str) -> anyhow::Result<Vec<MemoryRegion>> {
-	let dir = fs::read_dir(path)?;
+// the readable mappings without a file behind them, which is where the game keeps its data
+fn anonymous_regions(maps: &str) -> Vec<MemoryRegion> {
+	parse_maps(maps)
+		.into_iter()
+		.filter(|(inode, region)| *inode == 0 && region.permissions.starts_with('r'))
+		.map(|(_, region)| region)
+		.collect()
+}
 
+// parses the text of /proc/<pid>/maps into every mapping and its inode
+fn parse_maps(maps: &str) -> Vec<(isize, MemoryRegion)> {
 	let mut res = Vec::new();
 
-	for entry in dir {
-		let entry = entry?;
+	for line in maps.lines() {
+		// anonymous mappings have no path. the kernel only pads the line with spaces up to the path column
+		// when there is a path to print, so without the fallback exactly the regions the scan needs are skipped
+		let parsed = scanf!(
+			line,
+			"{usize:x}-{usize:x} {&str} {usize:x} {&str} {isize}{&str}"
+		)
+		.or_else(|_| {
+			scanf!(line, "{usize:x}-{usize:x} {&str} {usize:x} {&str} {isize}").map(
+				|(begin, end, perms, offset, device, inode)| {
+					(begin, end, perms, offset, device, inode, "")
+				},
+			)
+		});
+
+		match parsed {
+			Err(_) => continue,
+			Ok((begin, end, permissions, _offset, _device, inode, path)) => {
+				let reg = MemoryRegion::new(begin, end, permissions, path.trim(), line);
+				res.push((inode, reg));
+			}
+		};
+	}
 
-		let data = fs::read(entry.path())?;
+	res
+}
 
-		let reg = MemoryRegion::from_vec(
-			data,
-			entry.file_name().to_str().unwrap(),
-			entry.path().to_str().unwrap(),
-		);
+/// all mappings in the text of /proc/<pid>/maps, including the ones backed by files and the unreadable ones
+pub fn mapped_regions(maps: &str) -> Vec<MemoryRegion> {
+	parse_maps(maps)
+		.into_iter()
+		.map(|(_, region)| region)
+		.collect()
+}
 
-		res.push(reg);
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_mapped_regions() {
+		let maps = "\
+140000000-140001000 r-xp 00000000 00:1f 1234     /games/MonsterHunterWorld.exe
+7f0000000000-7f0000002000 rw-p 00000000 00:00 0 
+7f0000002000-7f0000003000 ---p 00000000 00:00 0
+garbage";
+
+		let regions = mapped_regions(maps);
+		assert_eq!(regions.len(), 3);
+		assert_eq!(regions[0].get_begin(), 0x140000000);
+		assert_eq!(&*regions[0].path, "/games/MonsterHunterWorld.exe");
+		assert_eq!(regions[1].get_end(), 0x7f0000002000);
+		assert_eq!(&*regions[1].path, "");
+		assert_eq!(&*regions[2].permissions, "---p");
 	}
 
-	Ok(res)
+	#[test]
+	fn test_anonymous_regions() {
+		// with and without the padding after the inode
+		let maps = "\
+140000000-140001000 r-xp 00000000 00:1f 1234     /games/MonsterHunterWorld.exe
+7f0000000000-7f0000002000 rw-p 00000000 00:00 0 
+7f0000002000-7f0000003000 rw-p 00000000 00:00 0
+7f0000003000-7f0000004000 ---p 00000000 00:00 0";
+
+		let regions = anonymous_regions(maps);
+		let begins: Vec<usize> = regions.iter().map(|r| r.get_begin()).collect();
+		assert_eq!(begins, vec![0x7f0000000000, 0x7f0000002000]);
+	}
 }
@@ -1,7 +1,8 @@
-use super::region::MemoryRegion;
+use super::{reader::MemoryReader, region::MemoryRegion, x86::resolve_operand};
+use crate::error::{Error, Result};
 use std::fmt::Display;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PatternType {
 	PlayerName,
 	CurrentPlayerName,
@@ -40,69 +41,299 @@ impl Display for MemoryLocation {
 	}
 }
 
+/// decides which of multiple matches of a signature is the right one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+	// the signature should match exactly this many times, more matches make it ambiguous
+	ExpectedCount(usize),
+	// the match has to be code
+	Executable,
+	// the rip-relative operand of the match has to point to mapped memory holding null or a mapped address,
+	// null is fine since the games structures may not be allocated yet
+	ValidPointer,
+}
+
+impl Rule {
+	fn accepts<R: MemoryReader + ?Sized>(&self, m: &Match, reader: &R) -> bool {
+		match self {
+			Rule::ExpectedCount(_) => true,
+			Rule::Executable => m.permissions.contains('x'),
+			Rule::ValidPointer => {
+				let Some(target) = m.target else {
+					return false;
+				};
+				match reader.read(target, size_of::<u64>()) {
+					Ok(mem) => {
+						let pointer = u64::from_le_bytes(mem[..].try_into().unwrap()) as usize;
+						pointer == 0 || reader.read(pointer, 1).is_ok()
+					}
+					Err(_) => false,
+				}
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Match {
+	pub location: MemoryLocation,
+	// permissions and path of the region the match is in
+	pub permissions: Box<str>,
+	pub path: Box<str>,
+	// the address the rip-relative operand of the match points to
+	pub target: Option<usize>,
+	// the rules this match broke, empty if it was accepted
+	pub rejected: Vec<Rule>,
+}
+
 #[derive(Debug)]
 pub struct PatternGetter {
 	pub mem_location: Option<MemoryLocation>,
 	pub pattern_type: PatternType,
+	// every match in every searched region
+	pub matches: Vec<Match>,
+	pub rules: &'static [Rule],
+	// which rip-relative operands lead to the data, see `resolve`
+	pub operands: &'static [usize],
 	find_func: fn(&[u8]) -> Option<usize>,
 }
 
 impl PatternGetter {
-	pub fn new(pattern_type: PatternType, find_func: fn(&[u8]) -> Option<usize>) -> Self {
+	pub fn new(
+		pattern_type: PatternType,
+		find_func: fn(&[u8]) -> Option<usize>,
+		rules: &'static [Rule],
+	) -> Self {
 		PatternGetter {
 			pattern_type,
 			find_func,
+			rules,
+			operands: &[0],
+			matches: Vec::new(),
 			mem_location: None,
 		}
 	}
 
-	pub fn search(&mut self, mem_region: &MemoryRegion) -> anyhow::Result<()> {
+	pub fn with_operands(mut self, operands: &'static [usize]) -> Self {
+		self.operands = operands;
+		self
+	}
+
+	// follows the operands from the match: each one is the index of a rip-relative operand
+	// in the instructions at the address the previous one led to,
+	// so `&[1, 0]` is the first operand in the function called by the second operand
+	fn resolve_at<R: MemoryReader + ?Sized>(&self, reader: &R, addr: usize) -> Result<usize> {
+		self.operands
+			.iter()
+			.try_fold(addr, |addr, n| resolve_operand(reader, addr, *n))
+	}
+
+	/// the address the operands of the used match lead to
+	pub fn resolve<R: MemoryReader + ?Sized>(&self, reader: &R) -> Result<usize> {
+		let Some(loc) = self.mem_location else {
+			return Err(Error::PatternNotFound(self.pattern_type));
+		};
+
+		// the code doesn't change while the game runs, so the target found by apply_rules stays valid
+		let target = self
+			.matches
+			.iter()
+			.find(|m| m.location.address == loc.address)
+			.and_then(|m| m.target);
+		match target {
+			Some(target) => Ok(target),
+			None => self.resolve_at(reader, loc.address),
+		}
+	}
+
+	/// adds all matches in the region, the first match of all searched regions is used until `apply_rules` is called
+	pub fn search(&mut self, mem_region: &MemoryRegion) -> Result<()> {
 		let data = match &mem_region.data {
 			Some(data) => data,
-			None => return Err(anyhow::anyhow!("Memory region has no data")),
+			None => return Err(Error::NoRegionData),
 		};
 
-		match (self.find_func)(data) {
-			Some(res) => {
-				let loc = MemoryLocation::new(mem_region.get_begin(), res);
-				self.mem_location = Some(loc);
-			}
-			None => {
-				self.mem_location = None;
-				return Err(anyhow::anyhow!("not found"));
-			}
-		};
+		let found = self.matches.len();
+		let mut start = 0;
+		while let Some(res) = (self.find_func)(&data[start..]) {
+			let location = MemoryLocation::new(mem_region.get_begin(), start + res);
+			self.matches.push(Match {
+				location,
+				permissions: mem_region.permissions.clone(),
+				path: mem_region.path.clone(),
+				target: None,
+				rejected: Vec::new(),
+			});
+			start += res + 1;
+		}
+
+		if self.matches.len() == found {
+			return Err(Error::PatternNotFound(self.pattern_type));
+		}
+
+		if self.mem_location.is_none() {
+			self.mem_location = Some(self.matches[found].location);
+		}
 
 		Ok(())
 	}
-}
-
-pub fn get_search_index(first_bytes: &[u8], input: &[u8]) -> Option<usize> {
-	let mut inp = input;
 
-	let mut i = memchr::memchr(first_bytes[0], input)?;
-	let mut index_in_og = i;
-	let fb_len = first_bytes.len();
+	/// checks all matches against the rules of the pattern and uses the first accepted one.
+	/// if no match is accepted the first one is kept, since a wrong rule shouldn't break a working signature
+	pub fn apply_rules<R: MemoryReader + ?Sized>(&mut self, reader: &R) {
+		// only the signatures with pointers have rip-relative operands
+		let rip_relative = self.rules.contains(&Rule::ValidPointer);
+
+		for i in 0..self.matches.len() {
+			if rip_relative {
+				self.matches[i].target = self
+					.resolve_at(reader, self.matches[i].location.address)
+					.ok();
+			}
 
-	while i < input.len() - 2 {
-		if i >= inp.len() || fb_len + 8 > inp.len() {
-			return None;
+			let m = &self.matches[i];
+			let rejected = self
+				.rules
+				.iter()
+				.filter(|rule| !rule.accepts(m, reader))
+				.copied()
+				.collect();
+			self.matches[i].rejected = rejected;
 		}
 
-		let bytes_to_compare = &inp[i..fb_len + i];
+		let chosen = self
+			.accepted()
+			.next()
+			.or(self.matches.first())
+			.map(|m| m.location);
+		self.mem_location = chosen;
+	}
 
-		if *bytes_to_compare == *first_bytes {
-			return Some(index_in_og);
-		}
+	pub fn accepted(&self) -> impl Iterator<Item = &Match> {
+		self.matches.iter().filter(|m| m.rejected.is_empty())
+	}
 
-		inp = &inp[i + 1..];
+	pub fn expected_count(&self) -> usize {
+		self.rules
+			.iter()
+			.find_map(|rule| match rule {
+				Rule::ExpectedCount(count) => Some(*count),
+				_ => None,
+			})
+			.unwrap_or(1)
+	}
 
-		i = memchr::memchr(first_bytes[0], inp)?;
+	/// more matches passed the rules than expected, so the used one might be the wrong one
+	pub fn is_ambiguous(&self) -> bool {
+		self.accepted().count() > self.expected_count()
+	}
+}
 
-		index_in_og += i;
+// rules for the signatures that lead to the games data through a rip-relative operand
+const CODE_RULES: &[Rule] = &[Rule::ExpectedCount(1), Rule::Executable, Rule::ValidPointer];
+
+/// getters for all known patterns, the order is the same as the index constants in the binary
+pub fn all_pattern_getters() -> [PatternGetter; 8] {
+	[
+		PatternGetter::new(PatternType::PlayerName, find_player_name, CODE_RULES),
+		PatternGetter::new(
+			PatternType::CurrentPlayerName,
+			find_current_player_name,
+			CODE_RULES,
+		),
+		PatternGetter::new(PatternType::PlayerDamage, find_player_damage, CODE_RULES),
+		PatternGetter::new(PatternType::Monsters, find_monster, CODE_RULES),
+		PatternGetter::new(PatternType::PlayerBuff, find_player_buff, CODE_RULES),
+		// just a string, so there are no rules that could tell the matches apart
+		PatternGetter::new(PatternType::Emetta, find_emetta, &[]),
+		PatternGetter::new(
+			PatternType::PlayerNameLinux,
+			find_player_name_linux,
+			CODE_RULES,
+		),
+		PatternGetter::new(PatternType::LobbyStatus, find_lobby_status, CODE_RULES),
+	]
+}
+
+impl PatternType {
+	/// the signature the find_* function of this pattern searches for
+	pub fn signature(&self) -> &'static str {
+		SIGNATURES
+			.iter()
+			.find(|(pattern_type, _)| pattern_type == self)
+			.map(|(_, sig)| *sig)
+			.unwrap()
 	}
+}
 
-	None
+/// the signatures the find_* functions search for, with ?? as wildcards
+pub const SIGNATURES: [(PatternType, &str); 8] = [
+	(
+		PatternType::PlayerName,
+		"48 8B 0D ?? ?? ?? ?? 48 8D 54 24 38 C6 44 24 20 00 E8 ?? ?? ?? ?? 48 8B 5C 24 70 48 8B 7C 24 60 48 83 C4 68 C3",
+	),
+	(
+		PatternType::CurrentPlayerName,
+		"48 8B 0D ?? ?? ?? ?? 48 8D 55 ?? 45 31 C9 41 89 C0 E8",
+	),
+	(
+		PatternType::PlayerDamage,
+		"48 8B 0D ?? ?? ?? ?? E8 ?? ?? ?? ?? 48 8B D8 48 85 C0 75 04 33 C9",
+	),
+	(
+		PatternType::Monsters,
+		"48 8B 0D ?? ?? ?? ?? B2 01 E8 ?? ?? ?? ?? C6 83 ?? ?? ?? ?? ?? 48 8B 0D",
+	),
+	(
+		PatternType::PlayerBuff,
+		"48 8B 05 ?? ?? ?? ?? 41 8B 94 00 ?? ?? ?? ?? 89 57",
+	),
+	(
+		PatternType::LobbyStatus,
+		"48 8B 0D ?? ?? ?? ?? E8 ?? ?? ?? ?? 48 8B 4E ?? F3 0F 10 86 ?? ?? ?? ?? F3 0F 58 86 ?? ?? ?? ?? F3 0F 11 86 ?? ?? ?? ?? E8 ?? ?? ?? ?? 48 8B 4E",
+	),
+	(PatternType::Emetta, "45 6D 65 74 74 61"),
+	(
+		PatternType::PlayerNameLinux,
+		"48 8B 0D ?? ?? ?? ?? 48 8D 54 24 ?? ?? ?? ?? ?? ?? ?? ?? ?? ?? ?? ?? ?? ?? 48 8B 5C 24 60 48 83 C4 50 5F C3",
+	),
+];
+
+/// turns a signature into its bytes, None for the wildcards
+pub fn parse_signature(sig: &str) -> Option<Vec<Option<u8>>> {
+	sig.split_whitespace()
+		.map(|byte| match byte {
+			"??" => Some(None),
+			byte => u8::from_str_radix(byte, 16).ok().map(Some),
+		})
+		.collect()
+}
+
+// turns a signature into bytes, with `fill` for the wildcards
+#[cfg(test)]
+pub(crate) fn signature(sig: &str, fill: u8) -> Vec<u8> {
+	parse_signature(sig)
+		.unwrap()
+		.into_iter()
+		.map(|byte| byte.unwrap_or(fill))
+		.collect()
+}
+
+pub fn get_search_index(first_bytes: &[u8], input: &[u8]) -> Option<usize> {
+	memchr::memmem::find(input, first_bytes)
+}
+
+// returns the first position of the initial bytes where the rest of the pattern matches too.
+// `condition` gets the input starting at that position and at least `total_len` bytes long
+fn find_pattern(
+	input: &[u8],
+	initial_bytes: &[u8],
+	total_len: usize,
+	condition: impl Fn(&[u8]) -> bool,
+) -> Option<usize> {
+	memchr::memmem::find_iter(input, initial_bytes)
+		.take_while(|pos| input.len() - pos >= total_len)
+		.find(|pos| condition(&input[*pos..]))
 }
 
 // 48 8B 0D ?? ?? ?? ?? 48 8D 54 24 38 C6 44 24 20 00 E8 ?? ?? ?? ?? 48 8B 5C 24 70 48 8B 7C 24 60 48 83 C4 68 C3
@@ -110,11 +341,6 @@ pub fn find_player_name(input: &[u8]) -> Option<usize> {
 	const INITIAL_BYTES: [u8; 3] = [0x48, 0x8B, 0x0D];
 	const TOTAL_LEN: usize = 37;
 
-	let mut sliced;
-	let mut pos = get_search_index(&INITIAL_BYTES, input)?;
-	sliced = &input[pos..];
-	dbg!(&sliced[..TOTAL_LEN]);
-
 	const SKIP_1: usize = 4;
 
 	const POS_SECTION_2: usize = INITIAL_BYTES.len() + SKIP_1;
@@ -135,21 +361,7 @@ pub fn find_player_name(input: &[u8]) -> Option<usize> {
 			&& input[POS_SECTION_3..POS_SECTION_3 + SECTION_3.len()] == SECTION_3
 	};
 
-	let mut total_pos = 0;
-
-	while sliced.len() > TOTAL_LEN {
-		total_pos += pos;
-
-		if condition(sliced) {
-			return Some(total_pos);
-		} else {
-			sliced = &sliced[1..];
-			pos = get_search_index(&INITIAL_BYTES, sliced)?;
-			sliced = &sliced[pos..];
-			total_pos += 1;
-		}
-	}
-	None
+	find_pattern(input, &INITIAL_BYTES, TOTAL_LEN, condition)
 }
 
 // 48 8B 0D ?? ?? ?? ?? 48 8D 55 ?? 45 31 C9 41 89 C0 E8
@@ -157,10 +369,6 @@ pub fn find_current_player_name(input: &[u8]) -> Option<usize> {
 	const INITIAL_BYTES: [u8; 3] = [0x48, 0x8B, 0x0D];
 	const TOTAL_LEN: usize = 18;
 
-	let mut sliced;
-	let mut pos: usize = get_search_index(&INITIAL_BYTES, input)?;
-	sliced = &input[pos..];
-
 	const SKIP_1: usize = 4;
 
 	const POS_SECTION_2: usize = INITIAL_BYTES.len() + SKIP_1;
@@ -176,22 +384,7 @@ pub fn find_current_player_name(input: &[u8]) -> Option<usize> {
 			&& input[POS_SECTION_3..POS_SECTION_3 + SECTION_3.len()] == SECTION_3
 	};
 
-	let mut total_pos = 0;
-
-	while sliced.len() > TOTAL_LEN {
-		total_pos += pos;
-
-		if condition(sliced) {
-			return Some(total_pos);
-		} else {
-			sliced = &sliced[1..];
-			pos = get_search_index(&INITIAL_BYTES, sliced)?;
-			sliced = &sliced[pos..];
-			total_pos += 1;
-		}
-	}
-
-	None
+	find_pattern(input, &INITIAL_BYTES, TOTAL_LEN, condition)
 }
 
 // 48 8B 0D ?? ?? ?? ?? E8 ?? ?? ?? ?? 48 8B D8 48 85 C0 75 04 33 C9
@@ -199,10 +392,6 @@ pub fn find_player_damage(input: &[u8]) -> Option<usize> {
 	const INITIAL_BYTES: [u8; 3] = [0x48, 0x8B, 0x0D];
 	const TOTAL_LEN: usize = 22;
 
-	let mut sliced;
-	let mut pos = get_search_index(&INITIAL_BYTES, input)?;
-	sliced = &input[pos..];
-
 	const SKIP_1: usize = 4;
 
 	const POS_SECTION_2: usize = INITIAL_BYTES.len() + SKIP_1;
@@ -218,22 +407,7 @@ pub fn find_player_damage(input: &[u8]) -> Option<usize> {
 			&& input[POS_SECTION_3..POS_SECTION_3 + SECTION_3.len()] == SECTION_3
 	};
 
-	let mut total_pos = 0;
-
-	while sliced.len() > TOTAL_LEN {
-		total_pos += pos;
-
-		if condition(sliced) {
-			return Some(total_pos);
-		} else {
-			sliced = &sliced[1..];
-			pos = get_search_index(&INITIAL_BYTES, sliced)?;
-			sliced = &sliced[pos..];
-			total_pos += 1;
-		}
-	}
-
-	None
+	find_pattern(input, &INITIAL_BYTES, TOTAL_LEN, condition)
 }
 
 // 48 8B 0D ?? ?? ?? ?? B2 01 E8 ?? ?? ?? ?? C6 83 ?? ?? ?? ?? ?? 48 8B 0D
@@ -241,10 +415,6 @@ pub fn find_monster(input: &[u8]) -> Option<usize> {
 	const INITIAL_BYTES: [u8; 3] = [0x48, 0x8B, 0x0D];
 	const TOTAL_LEN: usize = 24;
 
-	let mut sliced;
-	let mut pos = get_search_index(&INITIAL_BYTES, input)?;
-	sliced = &input[pos..];
-
 	const SKIP_1: usize = 4;
 
 	const POS_SECTION_2: usize = INITIAL_BYTES.len() + SKIP_1;
@@ -266,22 +436,7 @@ pub fn find_monster(input: &[u8]) -> Option<usize> {
 			&& input[POS_SECTION_4..POS_SECTION_4 + SECTION_4.len()] == SECTION_4
 	};
 
-	let mut total_pos = 0;
-
-	while sliced.len() > TOTAL_LEN {
-		total_pos += pos;
-
-		if condition(sliced) {
-			return Some(total_pos);
-		} else {
-			sliced = &sliced[1..];
-			pos = get_search_index(&INITIAL_BYTES, sliced)?;
-			sliced = &sliced[pos..];
-			total_pos += 1;
-		}
-	}
-
-	None
+	find_pattern(input, &INITIAL_BYTES, TOTAL_LEN, condition)
 }
 
 // 48 8B 05 ?? ?? ?? ?? 41 8B 94 00 ?? ?? ?? ?? 89 57
@@ -289,10 +444,6 @@ pub fn find_player_buff(input: &[u8]) -> Option<usize> {
 	const INITIAL_BYTES: [u8; 3] = [0x48, 0x8B, 0x05];
 	const TOTAL_LEN: usize = 17;
 
-	let mut sliced;
-	let mut pos = get_search_index(&INITIAL_BYTES, input)?;
-	sliced = &input[pos..];
-
 	const SKIP_1: usize = 4;
 
 	const POS_SECTION_2: usize = INITIAL_BYTES.len() + SKIP_1;
@@ -308,32 +459,13 @@ pub fn find_player_buff(input: &[u8]) -> Option<usize> {
 			&& input[POS_SECTION_3..POS_SECTION_3 + SECTION_3.len()] == SECTION_3
 	};
 
-	let mut total_pos = 0;
-
-	while sliced.len() > TOTAL_LEN {
-		total_pos += pos;
-
-		if condition(sliced) {
-			return Some(total_pos);
-		} else {
-			sliced = &sliced[1..];
-			pos = get_search_index(&INITIAL_BYTES, sliced)?;
-			sliced = &sliced[pos..];
-			total_pos += 1;
-		}
-	}
-
-	None
+	find_pattern(input, &INITIAL_BYTES, TOTAL_LEN, condition)
 }
 
 // 48 8B 0D ?? ?? ?? ?? E8 ?? ?? ?? ?? 48 8B 4E ?? F3 0F 10 86 ?? ?? ?? ?? F3 0F 58 86 ?? ?? ?? ?? F3 0F 11 86 ?? ?? ?? ?? E8 ?? ?? ?? ?? 48 8B 4E
 pub fn find_lobby_status(input: &[u8]) -> Option<usize> {
 	const INITIAL_BYTES: [u8; 3] = [0x48, 0x8B, 0x0D];
-	const TOTAL_LEN: usize = 39;
-
-	let mut sliced;
-	let mut pos = get_search_index(&INITIAL_BYTES, input)?;
-	sliced = &input[pos..];
+	const TOTAL_LEN: usize = 48;
 
 	const SKIP_1: usize = 4;
 
@@ -380,22 +512,7 @@ pub fn find_lobby_status(input: &[u8]) -> Option<usize> {
 			&& input[POS_SECTION_8..POS_SECTION_8 + SECTION_8.len()] == SECTION_8
 	};
 
-	let mut total_pos = 0;
-
-	while sliced.len() > TOTAL_LEN {
-		total_pos += pos;
-
-		if condition(sliced) {
-			return Some(total_pos);
-		} else {
-			sliced = &sliced[1..];
-			pos = get_search_index(&INITIAL_BYTES, sliced)?;
-			sliced = &sliced[pos..];
-			total_pos += 1;
-		}
-	}
-
-	None
+	find_pattern(input, &INITIAL_BYTES, TOTAL_LEN, condition)
 }
 
 // 45 6D 65 74 74 61
@@ -409,10 +526,6 @@ pub fn find_player_name_linux(input: &[u8]) -> Option<usize> {
 	const INITIAL_BYTES: [u8; 3] = [0x48, 0x8B, 0x0D];
 	const TOTAL_LEN: usize = 36;
 
-	let mut sliced;
-	let mut pos = get_search_index(&INITIAL_BYTES, input)?;
-	sliced = &input[pos..];
-
 	const SKIP_1: usize = 4;
 
 	const POS_SECTION_2: usize = INITIAL_BYTES.len() + SKIP_1;
@@ -430,27 +543,186 @@ pub fn find_player_name_linux(input: &[u8]) -> Option<usize> {
 			&& input[POS_SECTION_3..POS_SECTION_3 + SECTION_3.len()] == SECTION_3
 	};
 
-	let mut total_pos = 0;
+	find_pattern(input, &INITIAL_BYTES, TOTAL_LEN, condition)
+}
 
-	while sliced.len() > TOTAL_LEN {
-		total_pos += pos;
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use proptest::{collection::vec, prelude::*, sample::select};
+
+	fn find_func(pattern_type: PatternType) -> fn(&[u8]) -> Option<usize> {
+		all_pattern_getters()
+			.into_iter()
+			.find(|g| g.pattern_type == pattern_type)
+			.unwrap()
+			.find_func
+	}
+
+	// bytes that show up in the signatures, so random input gets close to matching
+	fn signature_byte() -> impl Strategy<Value = u8> {
+		select(vec![
+			0x00, 0x01, 0x05, 0x0D, 0x45, 0x48, 0x4E, 0x55, 0x61, 0x6D, 0x74, 0x8B, 0x8D, 0xC3,
+			0xE8, 0xF3,
+		])
+	}
+
+	#[test]
+	fn test_apply_rules() {
+		let sig = signature(SIGNATURES[4].1, 0);
+		let region = |begin, permissions, placed: &[(usize, i32)]| {
+			let mut data = vec![0u8; 0x100];
+			for (offset, displacement) in placed {
+				data[*offset..*offset + sig.len()].copy_from_slice(&sig);
+				data[offset + 3..offset + 7].copy_from_slice(&displacement.to_le_bytes());
+			}
+			MemoryRegion::from_vec(begin, data, permissions, "", "")
+		};
+
+		// points to the start of the last region, which holds null
+		let valid = |addr: usize| (0x5000 - (addr + 7)) as i32;
+		let regions = [
+			region(0x1000, "rw-p", &[(0x10, valid(0x1010))]),
+			region(0x3000, "r-xp", &[(0x20, 0x7777777), (0x40, valid(0x3040))]),
+			region(0x5000, "rw-p", &[]),
+		];
 
-		if condition(sliced) {
-			return Some(total_pos);
-		} else {
-			sliced = &sliced[1..];
-			pos = get_search_index(&INITIAL_BYTES, sliced)?;
-			sliced = &sliced[pos..];
-			total_pos += 1;
+		let mut getter = all_pattern_getters()
+			.into_iter()
+			.find(|g| g.pattern_type == PatternType::PlayerBuff)
+			.unwrap();
+		for region in &regions {
+			let _ = getter.search(region);
 		}
+		assert_eq!(getter.matches.len(), 3);
+		assert_eq!(getter.mem_location.unwrap().address, 0x1010);
+
+		getter.apply_rules(&regions[..]);
+		assert_eq!(getter.matches[0].rejected, vec![Rule::Executable]);
+		assert_eq!(getter.matches[1].rejected, vec![Rule::ValidPointer]);
+		assert_eq!(getter.mem_location.unwrap().address, 0x3040);
+		assert!(!getter.is_ambiguous());
+
+		let mut emetta = PatternGetter::new(PatternType::Emetta, find_emetta, &[]);
+		let _ = emetta.search(&MemoryRegion::from_vec(
+			0,
+			b"Emetta Emetta".to_vec(),
+			"rw-p",
+			"",
+			"",
+		));
+		emetta.apply_rules(&regions[..]);
+		assert_eq!(emetta.matches.len(), 2);
+		assert!(emetta.is_ambiguous());
 	}
 
-	None
-}
+	#[test]
+	fn test_resolve_chain() {
+		let mut code = vec![0u8; 0x100];
+		let sig = signature(PatternType::PlayerDamage.signature(), 0);
+		code[0x10..0x10 + sig.len()].copy_from_slice(&sig);
+		// mov rcx,[rip+0x100], then call 0x1080
+		code[0x13..0x17].copy_from_slice(&0x100i32.to_le_bytes());
+		code[0x18..0x1C].copy_from_slice(&0x64i32.to_le_bytes());
+		// lea rax,[rip+0x179]; ret
+		code[0x80..0x88].copy_from_slice(&[0x48, 0x8D, 0x05, 0x79, 0x01, 0x00, 0x00, 0xC3]);
+		let region = MemoryRegion::from_vec(0x1000, code, "r-xp", "", "");
+
+		let mut getter = PatternGetter::new(PatternType::PlayerDamage, find_player_damage, &[]);
+		let _ = getter.search(&region);
+		assert_eq!(getter.resolve(&[region.clone()][..]).unwrap(), 0x1117);
+
+		// the first operand of the called function
+		let mut getter = getter.with_operands(&[1, 0]);
+		getter.apply_rules(&[region.clone()][..]);
+		assert_eq!(getter.resolve(&[region][..]).unwrap(), 0x1200);
+	}
 
-#[cfg(test)]
-mod tests {
-	use super::*;
+	proptest! {
+		#[test]
+		fn prop_never_panics(data in prop_oneof![vec(any::<u8>(), 0..512), vec(signature_byte(), 0..512)]) {
+			for (pattern_type, _) in SIGNATURES {
+				let find = find_func(pattern_type);
+				if let Some(pos) = find(&data) {
+					prop_assert!(pos < data.len());
+					// the match has to be found again when searching from it
+					prop_assert_eq!(find(&data[pos..]), Some(0));
+				}
+			}
+		}
+
+		#[test]
+		fn prop_planted_is_found(
+			which in 0..SIGNATURES.len(),
+			prefix in vec(any::<u8>(), 0..256),
+			suffix in vec(any::<u8>(), 0..64),
+			fill in any::<u8>(),
+		) {
+			let (pattern_type, sig) = SIGNATURES[which];
+			let find = find_func(pattern_type);
+
+			// without the first byte of any signature nothing in front of the planted one can match
+			let mut data: Vec<u8> = prefix.iter().map(|b| if *b == 0x48 || *b == 0x45 { 0 } else { *b }).collect();
+			let offset = data.len();
+			data.extend(signature(sig, fill));
+			data.extend(&suffix);
+
+			prop_assert_eq!(find(&data), Some(offset));
+			// right at the end of the input
+			prop_assert_eq!(find(&data[..data.len() - suffix.len()]), Some(offset));
+		}
+
+		#[test]
+		fn prop_truncated_never_matches(
+			which in 0..SIGNATURES.len(),
+			prefix in vec(any::<u8>(), 0..64),
+			cut in any::<prop::sample::Index>(),
+			fill in any::<u8>(),
+		) {
+			let (pattern_type, sig) = SIGNATURES[which];
+			let sig = signature(sig, fill);
+
+			// a signature cut off by the end of the region
+			let mut data: Vec<u8> = prefix.iter().map(|b| if *b == 0x48 || *b == 0x45 { 0 } else { *b }).collect();
+			data.extend(&sig[..cut.index(sig.len())]);
+
+			prop_assert_eq!(find_func(pattern_type)(&data), None);
+		}
+
+		#[test]
+		fn prop_planted_after_noise(
+			which in 0..SIGNATURES.len(),
+			prefix in vec(signature_byte(), 0..256),
+			fill in any::<u8>(),
+		) {
+			let (pattern_type, sig) = SIGNATURES[which];
+			let find = find_func(pattern_type);
+
+			let mut data = prefix.clone();
+			data.extend(signature(sig, fill));
+
+			// the noise might contain an earlier match, but never skip the planted one
+			let pos = find(&data);
+			prop_assert!(pos.is_some_and(|pos| pos <= prefix.len()));
+		}
+	}
+
+	#[test]
+	fn test_get_search_index() {
+		let bytes = [0x45, 0x6D];
+		assert_eq!(get_search_index(&bytes, &[]), None);
+		assert_eq!(get_search_index(&bytes, &[0x45]), None);
+		assert_eq!(get_search_index(&bytes, &[0x45, 0x6D]), Some(0));
+		// false starts in front of the match must not shift the result
+		assert_eq!(
+			get_search_index(&bytes, &[0x45, 0x45, 0x00, 0x45, 0x6D]),
+			Some(3)
+		);
+		assert_eq!(
+			find_emetta(&[0x00, 0x45, 0x6D, 0x65, 0x74, 0x74, 0x61]),
+			Some(1)
+		);
+	}
 
 	#[test]
 	fn test_find_player_name() {
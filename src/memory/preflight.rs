@@ -0,0 +1,268 @@
+use super::{get_memory_regions, region::read_memory};
+use crate::error::{Error, Result};
+use nix::unistd::Pid;
+use std::{env, fmt::Display, fs, path::Path};
+
+const PTRACE_SCOPE: &str = "/proc/sys/kernel/yama/ptrace_scope";
+const CAP_SYS_PTRACE: u32 = 19;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckStatus {
+	Ok,
+	Warn,
+	Fail,
+}
+
+impl Display for CheckStatus {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			CheckStatus::Ok => write!(f, "OK"),
+			CheckStatus::Warn => write!(f, "WARN"),
+			CheckStatus::Fail => write!(f, "FAIL"),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct Check {
+	pub name: &'static str,
+	pub status: CheckStatus,
+	pub message: String,
+	pub hint: Option<String>,
+}
+
+impl Check {
+	fn new(name: &'static str, status: CheckStatus, message: String) -> Self {
+		Self {
+			name,
+			status,
+			message,
+			hint: None,
+		}
+	}
+
+	fn with_hint(mut self, hint: String) -> Self {
+		self.hint = Some(hint);
+		self
+	}
+}
+
+fn setcap_hint() -> String {
+	let exe = env::current_exe()
+		.map(|exe| exe.to_string_lossy().into_owned())
+		.unwrap_or_else(|_| String::from("/path/to/linux-hunter-rs"));
+	format!("sudo setcap cap_sys_ptrace=eip {}", exe)
+}
+
+// reads a field like "Uid:" from a /proc/<pid>/status file
+fn status_field(status_path: &str, field: &str) -> Option<String> {
+	let status = fs::read_to_string(status_path).ok()?;
+	status
+		.lines()
+		.find_map(|line| line.strip_prefix(field))
+		.map(|value| value.trim().to_string())
+}
+
+fn has_cap_sys_ptrace(cap_eff: &str) -> bool {
+	u64::from_str_radix(cap_eff, 16).is_ok_and(|caps| caps & (1 << CAP_SYS_PTRACE) != 0)
+}
+
+// the first column of the "Uid:" field is the real uid
+fn uid_of(status_path: &str) -> Option<u32> {
+	status_field(status_path, "Uid:")?
+		.split_whitespace()
+		.next()?
+		.parse()
+		.ok()
+}
+
+fn in_sandbox() -> Option<&'static str> {
+	if Path::new("/.flatpak-info").exists() {
+		return Some("Flatpak");
+	}
+	if env::vars().any(|(key, _)| key.starts_with("PRESSURE_VESSEL_"))
+		|| Path::new("/run/pressure-vessel").exists()
+	{
+		return Some("pressure-vessel");
+	}
+	None
+}
+
+fn check_ptrace_scope(cap_sys_ptrace: bool) -> Check {
+	let scope = match fs::read_to_string(PTRACE_SCOPE) {
+		Ok(scope) => scope.trim().to_string(),
+		Err(_) => {
+			return Check::new(
+				"ptrace_scope",
+				CheckStatus::Ok,
+				String::from("Yama is not enabled"),
+			)
+		}
+	};
+
+	match (scope.as_str(), cap_sys_ptrace) {
+		("0", _) => Check::new(
+			"ptrace_scope",
+			CheckStatus::Ok,
+			String::from("0 (classic ptrace permissions)"),
+		),
+		("1" | "2", true) => Check::new(
+			"ptrace_scope",
+			CheckStatus::Ok,
+			format!("{} (allowed through CAP_SYS_PTRACE)", scope),
+		),
+		("1", false) => Check::new(
+			"ptrace_scope",
+			CheckStatus::Fail,
+			String::from("1 (only child processes may be read)"),
+		)
+		.with_hint(format!(
+			"run '{}' or 'sudo sysctl kernel.yama.ptrace_scope=0'",
+			setcap_hint()
+		)),
+		("2", false) => Check::new(
+			"ptrace_scope",
+			CheckStatus::Fail,
+			String::from("2 (only processes with CAP_SYS_PTRACE may read memory)"),
+		)
+		.with_hint(format!("run '{}'", setcap_hint())),
+		("3", _) => Check::new(
+			"ptrace_scope",
+			CheckStatus::Fail,
+			String::from("3 (reading process memory is disabled)"),
+		)
+		.with_hint(String::from(
+			"ptrace_scope 3 can't be lowered at runtime, set kernel.yama.ptrace_scope to 0 in /etc/sysctl.d and reboot",
+		)),
+		(scope, _) => Check::new(
+			"ptrace_scope",
+			CheckStatus::Warn,
+			format!("unknown value '{}'", scope),
+		),
+	}
+}
+
+fn check_capability() -> (Check, bool) {
+	let cap_sys_ptrace =
+		status_field("/proc/self/status", "CapEff:").is_some_and(|caps| has_cap_sys_ptrace(&caps));
+
+	let check = match cap_sys_ptrace {
+		true => Check::new("CAP_SYS_PTRACE", CheckStatus::Ok, String::from("present")),
+		false => Check::new(
+			"CAP_SYS_PTRACE",
+			CheckStatus::Ok,
+			String::from("not present (only needed if ptrace_scope > 0)"),
+		),
+	};
+
+	(check, cap_sys_ptrace)
+}
+
+fn check_sandbox(pid: Option<Pid>) -> Check {
+	let own_ns = fs::read_link("/proc/self/ns/pid").ok();
+	let game_ns = pid.and_then(|pid| fs::read_link(format!("/proc/{}/ns/pid", pid)).ok());
+
+	match (in_sandbox(), own_ns, game_ns) {
+		(_, Some(own), Some(game)) if own != game => Check::new(
+			"sandbox",
+			CheckStatus::Warn,
+			String::from("the game runs in a different pid namespace"),
+		)
+		.with_hint(String::from(
+			"run linux-hunter-rs on the host, or inside the same Flatpak/pressure-vessel sandbox as the game",
+		)),
+		(Some(sandbox), ..) => Check::new(
+			"sandbox",
+			CheckStatus::Warn,
+			format!("running inside {}", sandbox),
+		)
+		.with_hint(String::from(
+			"if the game can't be read, run linux-hunter-rs on the host or in the same sandbox as the game",
+		)),
+		(None, ..) => Check::new("sandbox", CheckStatus::Ok, String::from("not sandboxed")),
+	}
+}
+
+fn check_user(pid: Pid) -> Check {
+	let own = uid_of("/proc/self/status");
+	let game = uid_of(&format!("/proc/{}/status", pid));
+
+	match (own, game) {
+		(Some(own), Some(game)) if own != game && own != 0 => Check::new(
+			"user",
+			CheckStatus::Fail,
+			format!("the game runs as uid {}, but we run as uid {}", game, own),
+		)
+		.with_hint(String::from(
+			"run linux-hunter-rs as the same user as the game",
+		)),
+		(Some(_), Some(game)) => Check::new("user", CheckStatus::Ok, format!("uid {}", game)),
+		_ => Check::new(
+			"user",
+			CheckStatus::Warn,
+			String::from("can't determine the uid of the game"),
+		),
+	}
+}
+
+/// reads a few bytes from the game to verify that its memory is accessible at all
+pub fn test_read(pid: Pid) -> Result<()> {
+	let regions = get_memory_regions(pid, None)?;
+	let region = regions.first().ok_or(Error::InvalidRegions("empty"))?;
+	read_memory(pid, region.get_begin(), 8)?;
+	Ok(())
+}
+
+fn check_read(pid: Pid) -> Check {
+	match test_read(pid) {
+		Ok(_) => Check::new(
+			"memory read",
+			CheckStatus::Ok,
+			format!("read memory of pid {}", pid),
+		),
+		Err(e @ Error::PermissionDenied { .. }) => {
+			Check::new("memory read", CheckStatus::Fail, e.to_string()).with_hint(String::from(
+				"see the ptrace_scope, CAP_SYS_PTRACE and sandbox checks above",
+			))
+		}
+		Err(e) => Check::new("memory read", CheckStatus::Fail, e.to_string()),
+	}
+}
+
+/// runs all permission checks, the ones that need the game are skipped if `pid` is `None`
+pub fn run_checks(pid: Option<Pid>) -> Vec<Check> {
+	let (cap_check, cap_sys_ptrace) = check_capability();
+	let mut checks = vec![
+		check_ptrace_scope(cap_sys_ptrace),
+		cap_check,
+		check_sandbox(pid),
+	];
+
+	match pid {
+		Some(pid) => {
+			checks.push(check_user(pid));
+			checks.push(check_read(pid));
+		}
+		None => checks.push(Check::new(
+			"game",
+			CheckStatus::Warn,
+			String::from("MH:W is not running, skipping the checks that need it"),
+		)),
+	}
+
+	checks
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_has_cap_sys_ptrace() {
+		assert!(has_cap_sys_ptrace("0000000000080000"));
+		assert!(has_cap_sys_ptrace("000001ffffffffff"));
+		assert!(!has_cap_sys_ptrace("0000000000000000"));
+		assert!(!has_cap_sys_ptrace("0000000000040000"));
+		assert!(!has_cap_sys_ptrace("garbage"));
+	}
+}
@@ -0,0 +1,287 @@
+use super::{
+	dump,
+	region::{read_memory, read_memory_batch, MemoryRegion},
+};
+use crate::error::{Error, Result};
+use nix::{errno::Errno, unistd::Pid};
+use std::{
+	cell::{Cell, RefCell},
+	ops::Range,
+};
+
+/// something the memory of the game can be read from, either the running game or a dump of it
+pub trait MemoryReader {
+	fn read(&self, addr: usize, len: usize) -> Result<Box<[u8]>>;
+
+	/// reads all ranges, None for the ones that couldn't be read
+	fn read_ranges(&self, ranges: &[Range<usize>]) -> Result<Vec<Option<Box<[u8]>>>> {
+		Ok(ranges
+			.iter()
+			.map(|r| self.read(r.start, r.len()).ok())
+			.collect())
+	}
+}
+
+impl MemoryReader for Pid {
+	fn read(&self, addr: usize, len: usize) -> Result<Box<[u8]>> {
+		read_memory(*self, addr, len)
+	}
+
+	fn read_ranges(&self, ranges: &[Range<usize>]) -> Result<Vec<Option<Box<[u8]>>>> {
+		read_memory_batch(*self, ranges)
+	}
+}
+
+/// serves reads from the regions of a dump, so the game can be "read" without it running
+#[derive(Debug)]
+pub struct DumpReader {
+	regions: Vec<MemoryRegion>,
+}
+
+impl DumpReader {
+	pub fn new(mut regions: Vec<MemoryRegion>) -> Self {
+		regions.sort_by_key(MemoryRegion::get_begin);
+		Self { regions }
+	}
+
+	pub fn open(path: &str) -> Result<Self> {
+		Ok(Self::new(dump::load(path)?))
+	}
+}
+
+impl MemoryReader for DumpReader {
+	fn read(&self, addr: usize, len: usize) -> Result<Box<[u8]>> {
+		self.regions[..].read(addr, len)
+	}
+}
+
+// reads from regions that already have their data, they have to be sorted
+impl MemoryReader for [MemoryRegion] {
+	fn read(&self, addr: usize, len: usize) -> Result<Box<[u8]>> {
+		// the last region that starts at or before addr is the only one that can contain it
+		let i = self.partition_point(|r| r.get_begin() <= addr);
+		let region = i.checked_sub(1).map(|i| &self[i]);
+
+		match region.and_then(|r| Some((r, r.data.as_ref()?))) {
+			Some((region, data)) if addr.saturating_add(len) <= region.get_end() => {
+				let start = addr - region.get_begin();
+				Ok(Box::from(&data[start..start + len]))
+			}
+			// the same error the kernel gives us for unmapped memory
+			_ => Err(Error::ReadFailed {
+				addr,
+				source: Errno::EFAULT,
+			}),
+		}
+	}
+}
+
+/// passes reads through to another reader and remembers which memory was read
+pub struct RecordingReader<'a, R: MemoryReader + ?Sized> {
+	inner: &'a R,
+	ranges: RefCell<Vec<Range<usize>>>,
+}
+
+impl<'a, R: MemoryReader + ?Sized> RecordingReader<'a, R> {
+	pub fn new(inner: &'a R) -> Self {
+		Self {
+			inner,
+			ranges: RefCell::new(Vec::new()),
+		}
+	}
+
+	pub fn into_ranges(self) -> Vec<Range<usize>> {
+		self.ranges.into_inner()
+	}
+}
+
+impl<R: MemoryReader + ?Sized> MemoryReader for RecordingReader<'_, R> {
+	fn read(&self, addr: usize, len: usize) -> Result<Box<[u8]>> {
+		self.ranges
+			.borrow_mut()
+			.push(addr..addr.saturating_add(len));
+		self.inner.read(addr, len)
+	}
+}
+
+/// the memory the last tick read. the addresses rarely change between ticks,
+/// so the next tick can fetch all of it with one batched read up front instead of a syscall for every field
+#[derive(Debug, Default)]
+pub struct ReadPlan {
+	ranges: Vec<Range<usize>>,
+	// everything the last run read came from the batched read, so it was read at (almost) the same instant
+	complete: bool,
+}
+
+impl ReadPlan {
+	/// runs `f` with a reader that serves the planned memory from one batched read,
+	/// afterwards the plan contains everything `f` read
+	pub fn run<R: MemoryReader + ?Sized, T>(
+		&mut self,
+		reader: &R,
+		f: impl FnOnce(&Prefetched<R>) -> Result<T>,
+	) -> Result<T> {
+		let prefetched = Prefetched::new(reader, &self.ranges)?;
+		let res = f(&prefetched);
+		self.complete = prefetched.misses.get() == 0;
+		self.ranges = coalesce(prefetched.recorder.into_ranges());
+		res
+	}
+
+	/// if the last run got all its memory from the batched read, instead of reading some of it later on
+	pub fn is_complete(&self) -> bool {
+		self.complete
+	}
+}
+
+/// memory that was read in advance, reads of anything else go to the inner reader
+pub struct Prefetched<'a, R: MemoryReader + ?Sized> {
+	regions: Vec<MemoryRegion>,
+	recorder: RecordingReader<'a, R>,
+	// successful reads that weren't prefetched
+	misses: Cell<usize>,
+}
+
+impl<'a, R: MemoryReader + ?Sized> Prefetched<'a, R> {
+	pub fn new(inner: &'a R, ranges: &[Range<usize>]) -> Result<Self> {
+		let ranges = coalesce(ranges.to_vec());
+		let regions = ranges
+			.iter()
+			.zip(inner.read_ranges(&ranges)?)
+			.filter_map(|(range, data)| {
+				Some(MemoryRegion::from_vec(
+					range.start,
+					data?.into_vec(),
+					"",
+					"",
+					"",
+				))
+			})
+			.collect();
+
+		Ok(Self {
+			regions,
+			recorder: RecordingReader::new(inner),
+			misses: Cell::new(0),
+		})
+	}
+}
+
+impl<R: MemoryReader + ?Sized> MemoryReader for Prefetched<'_, R> {
+	fn read(&self, addr: usize, len: usize) -> Result<Box<[u8]>> {
+		match self.regions[..].read(addr, len) {
+			Ok(mem) => {
+				self.recorder
+					.ranges
+					.borrow_mut()
+					.push(addr..addr.saturating_add(len));
+				Ok(mem)
+			}
+			Err(_) => {
+				let mem = self.recorder.read(addr, len)?;
+				self.misses.set(self.misses.get() + 1);
+				Ok(mem)
+			}
+		}
+	}
+}
+
+/// sorts ranges and merges the ones that overlap or touch
+pub fn coalesce(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+	ranges.retain(|r| !r.is_empty());
+	ranges.sort_by_key(|r| r.start);
+
+	let mut res: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+	for range in ranges {
+		match res.last_mut() {
+			Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+			_ => res.push(range),
+		}
+	}
+
+	res
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_dump_reader() {
+		let reader = DumpReader::new(vec![
+			MemoryRegion::from_vec(0x2000, vec![2; 0x100], "rw-p", "", ""),
+			MemoryRegion::from_vec(0x1000, (0..=255).collect(), "rw-p", "", ""),
+		]);
+
+		assert_eq!(&*reader.read(0x1010, 4).unwrap(), &[0x10, 0x11, 0x12, 0x13]);
+		assert_eq!(&*reader.read(0x20FF, 1).unwrap(), &[2]);
+		assert!(reader.read(0x10FF, 2).is_err());
+		assert!(reader.read(0x0FFF, 1).is_err());
+		assert!(reader.read(0x3000, 1).is_err());
+		assert!(reader.read(usize::MAX, 2).is_err());
+	}
+
+	// counts the syscalls reading from the game would need
+	struct Counting {
+		inner: DumpReader,
+		reads: Cell<usize>,
+	}
+
+	impl MemoryReader for Counting {
+		fn read(&self, addr: usize, len: usize) -> Result<Box<[u8]>> {
+			self.reads.set(self.reads.get() + 1);
+			self.inner.read(addr, len)
+		}
+
+		fn read_ranges(&self, ranges: &[Range<usize>]) -> Result<Vec<Option<Box<[u8]>>>> {
+			self.reads.set(self.reads.get() + 1);
+			Ok(ranges
+				.iter()
+				.map(|r| self.inner.read(r.start, r.len()).ok())
+				.collect())
+		}
+	}
+
+	#[test]
+	fn test_read_plan() {
+		let reader = Counting {
+			inner: DumpReader::new(vec![MemoryRegion::from_vec(
+				0x1000,
+				(0..=255).collect(),
+				"rw-p",
+				"",
+				"",
+			)]),
+			reads: Cell::new(0),
+		};
+		let tick = |mem: &Prefetched<Counting>| -> Result<Vec<Box<[u8]>>> {
+			Ok(vec![
+				mem.read(0x1000, 4)?,
+				mem.read(0x1004, 4)?,
+				mem.read(0x1080, 1)?,
+			])
+		};
+
+		let mut plan = ReadPlan::default();
+		let first = plan.run(&reader, tick).unwrap();
+		assert_eq!(plan.ranges, vec![0x1000..0x1008, 0x1080..0x1081]);
+		assert!(!plan.is_complete());
+
+		reader.reads.set(0);
+		assert_eq!(plan.run(&reader, tick).unwrap(), first);
+		assert_eq!(reader.reads.get(), 1);
+		assert!(plan.is_complete());
+
+		// memory that went away is read directly, and fails like it would without the plan
+		plan.ranges.push(0x2000..0x2004);
+		assert!(plan.run(&reader, |mem| mem.read(0x2000, 4)).is_err());
+	}
+
+	#[test]
+	fn test_coalesce() {
+		assert_eq!(
+			coalesce(vec![10..20, 0..5, 5..8, 15..30, 40..40, 50..60]),
+			vec![0..8, 10..30, 50..60]
+		);
+	}
+}
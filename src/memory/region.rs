@@ -1,17 +1,18 @@
+use super::reader::MemoryReader;
+use crate::error::{Error, Result};
 use nix::{
+	errno::Errno,
 	sys::uio::{process_vm_readv, RemoteIoVec},
 	unistd::Pid,
 };
-use std::{
-	fs::File,
-	io::{IoSliceMut, Write},
-};
-use tracing::{debug, warn};
+use std::{io::IoSliceMut, ops::Range};
 
 #[derive(Debug, Clone)]
 pub struct MemoryRegion {
 	begin: usize,
 	end: usize,
+	pub permissions: Box<str>,
+	pub path: Box<str>,
 	pub debug_name: String,
 	pub debug_info: String,
 	pub data: Option<Box<[u8]>>,
@@ -19,22 +20,32 @@ pub struct MemoryRegion {
 }
 
 impl MemoryRegion {
-	pub fn new(begin: usize, end: usize, debug_name: &str, debug_info: &str) -> Self {
+	pub fn new(begin: usize, end: usize, permissions: &str, path: &str, debug_info: &str) -> Self {
 		MemoryRegion {
 			begin,
 			end,
-			debug_name: debug_name.to_string(),
+			permissions: Box::from(permissions),
+			path: Box::from(path),
+			debug_name: format!("{:x}", begin),
 			debug_info: debug_info.to_string(),
 			data: None,
 			from_vec: false,
 		}
 	}
 
-	pub fn from_vec(data: Vec<u8>, debug_name: &str, debug_info: &str) -> Self {
+	pub fn from_vec(
+		begin: usize,
+		data: Vec<u8>,
+		permissions: &str,
+		path: &str,
+		debug_info: &str,
+	) -> Self {
 		MemoryRegion {
-			begin: 0,
-			end: data.len(),
-			debug_name: debug_name.to_string(),
+			begin,
+			end: begin + data.len(),
+			permissions: Box::from(permissions),
+			path: Box::from(path),
+			debug_name: format!("{:x}", begin),
 			debug_info: debug_info.to_string(),
 			data: Some(data.into_boxed_slice()),
 			from_vec: true,
@@ -45,19 +56,11 @@ impl MemoryRegion {
 		self.begin
 	}
 
-	fn dump_mem(&self, path: &str) -> anyhow::Result<()> {
-		if let Some(data) = &self.data {
-			let path = path.to_string() + "/" + self.debug_name.as_str() + ".bin";
-			let mut file = File::create(path)?;
-
-			file.write_all(data)?;
-			file.flush()?;
-		}
-
-		Ok(())
+	pub fn get_end(&self) -> usize {
+		self.end
 	}
 
-	pub fn fill_data(&mut self, pid: Pid, dump_mem: Option<&str>) -> anyhow::Result<()> {
+	pub fn fill_data(&mut self, pid: Pid) -> Result<()> {
 		if self.from_vec {
 			return Ok(());
 		}
@@ -69,17 +72,11 @@ impl MemoryRegion {
 			}
 		}
 
-		if let Some(path) = dump_mem {
-			if let Err(e) = self.dump_mem(path) {
-				warn!("Failed to dump memory: {}", e);
-			}
-		}
-
 		Ok(())
 	}
 }
 
-pub fn read_memory(pid: Pid, start: usize, length: usize) -> anyhow::Result<Box<[u8]>> {
+pub fn read_memory(pid: Pid, start: usize, length: usize) -> Result<Box<[u8]>> {
 	let mut buf = vec![0u8; length];
 
 	let local = IoSliceMut::new(&mut buf);
@@ -88,41 +85,89 @@ pub fn read_memory(pid: Pid, start: usize, length: usize) -> anyhow::Result<Box<
 		len: length,
 	};
 
-	let read_size = process_vm_readv(pid, &mut [local], &[remote])?;
+	let read_size = process_vm_readv(pid, &mut [local], &[remote])
+		.map_err(|e| Error::from_errno(e, pid, start))?;
 
 	if read_size != length {
-		return Err(anyhow::anyhow!(
-			"Read {} bytes instead of {}",
-			read_size,
-			length
-		));
+		return Err(Error::PartialRead {
+			got: read_size,
+			want: length,
+		});
 	}
 
 	Ok(buf.into_boxed_slice())
 }
 
+/// reads all ranges with as few syscalls as possible, None for the ranges that couldn't be read
+pub fn read_memory_batch(pid: Pid, ranges: &[Range<usize>]) -> Result<Vec<Option<Box<[u8]>>>> {
+	// the kernel refuses more iovecs than this in one call
+	const IOV_MAX: usize = 1024;
+
+	let mut bufs: Vec<Vec<u8>> = ranges.iter().map(|r| vec![0u8; r.len()]).collect();
+	let mut res = vec![None; ranges.len()];
+
+	let mut next = 0;
+	while next < ranges.len() {
+		let end = ranges.len().min(next + IOV_MAX);
+
+		let mut local: Vec<IoSliceMut> = bufs[next..end]
+			.iter_mut()
+			.map(|buf| IoSliceMut::new(buf))
+			.collect();
+		let remote: Vec<RemoteIoVec> = ranges[next..end]
+			.iter()
+			.map(|r| RemoteIoVec {
+				base: r.start,
+				len: r.len(),
+			})
+			.collect();
+
+		let mut read = match process_vm_readv(pid, &mut local, &remote) {
+			Ok(read) => read,
+			// not even the first range could be read
+			Err(Errno::EFAULT) => 0,
+			Err(e) => return Err(Error::from_errno(e, pid, ranges[next].start)),
+		};
+		drop(local);
+
+		// the kernel stops at the first range it can't read completely, the ones before it were read in full
+		while next < end && read >= ranges[next].len() {
+			read -= ranges[next].len();
+			res[next] = Some(std::mem::take(&mut bufs[next]).into_boxed_slice());
+			next += 1;
+		}
+
+		// skip the one that failed and try again with the rest, if the batch didn't just end
+		if next < end {
+			next += 1;
+		}
+	}
+
+	Ok(res)
+}
+
 #[macro_export]
 macro_rules! read_mem_to_type {
-	($pid:expr, $start:expr, $t:ty) => {{
-		let ptr_loc: Box<[u8]> = read_memory($pid, $start, 4)?;
+	($mem:expr, $start:expr, $t:ty) => {{
+		let mem: Box<[u8]> =
+			$crate::memory::reader::MemoryReader::read($mem, $start, size_of::<$t>())?;
 
 		use tracing::debug;
-		debug!("ptr_loc: {:02X?}", ptr_loc);
-
-		let ptr_ptr: *mut [u8; size_of::<$t>()] =
-			Box::into_raw(ptr_loc) as *mut [u8; size_of::<$t>()];
-		// Create a slice from the pointer and interpret it as type T
-		// this is always safe, since we always get data of the right size
-		let sliced_slice: $t = unsafe { std::ptr::read(ptr_ptr as *const $t) };
-		sliced_slice
+		debug!("mem: {:02X?}", mem);
+
+		// the reader always returns exactly the requested amount of bytes
+		<$t>::from_le_bytes(mem[..].try_into().unwrap())
 	}};
 }
 
-pub fn verify_regions(regions: &[MemoryRegion]) -> anyhow::Result<()> {
-	let mut prev_beg = regions[0].begin;
+pub fn verify_regions(regions: &[MemoryRegion]) -> Result<()> {
+	let mut prev_beg = match regions.first() {
+		Some(region) => region.begin,
+		None => return Err(Error::InvalidRegions("empty")),
+	};
 	for region in regions.iter().skip(1) {
 		if region.begin < prev_beg {
-			return Err(anyhow::anyhow!("Invalid region sequence - order"));
+			return Err(Error::InvalidRegions("order"));
 		}
 
 		prev_beg = region.begin;
@@ -131,21 +176,77 @@ pub fn verify_regions(regions: &[MemoryRegion]) -> anyhow::Result<()> {
 	Ok(())
 }
 
-pub fn load_rel_addr(pid: Pid, addr: usize) -> anyhow::Result<usize> {
-	const OP_CODE_LEN: usize = 3;
-	const PARAM_LEN: usize = 4;
-	const INSTRUCTION_LEN: usize = OP_CODE_LEN + PARAM_LEN;
+// follows a pointer chain: for every offset the pointer at the current address is read and the offset added to it
+pub fn follow_pointers<R: MemoryReader + ?Sized>(
+	mem: &R,
+	addr: usize,
+	offsets: &[usize],
+) -> Result<usize> {
+	let mut addr = addr;
+	for offset in offsets {
+		let mem = mem.read(addr, size_of::<u64>())?;
+		let pointer = u64::from_le_bytes(mem[..].try_into().unwrap()) as usize;
+		// the pointer comes from the game and may be garbage
+		addr = pointer
+			.checked_add(*offset)
+			.ok_or(Error::InvalidPointer { addr: pointer })?;
+	}
 
-	let operand = read_mem_to_type!(pid, addr + OP_CODE_LEN, u32);
-	let mut big_operand = operand as u64;
+	Ok(addr)
+}
 
-	debug!("operand: {}", big_operand);
-	debug!("big operand: {}", big_operand);
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use nix::unistd::getpid;
+
+	#[test]
+	fn test_read_memory_batch() {
+		let data: Vec<u8> = (0..=255).collect();
+		let addr = data.as_ptr() as usize;
+
+		// an unmapped range in the middle must not stop the ones after it from being read
+		let ranges = [
+			addr..addr + 16,
+			0..8,
+			addr + 128..addr + 132,
+			addr + 16..addr + 16,
+		];
+		let res = read_memory_batch(getpid(), &ranges).unwrap();
+
+		assert_eq!(res[0].as_deref(), Some(&data[..16]));
+		assert_eq!(res[1], None);
+		assert_eq!(res[2].as_deref(), Some(&data[128..132]));
+		assert_eq!(res[3].as_deref(), Some(&[][..]));
+	}
 
-	if big_operand > i32::MAX as u64 {
-		big_operand |= 0xffffffff00000000;
-		debug!("new big operand: {}", big_operand);
+	#[test]
+	fn test_follow_pointers() {
+		let mut data = vec![0u8; 0x20];
+		data[..8].copy_from_slice(&0x1010u64.to_le_bytes());
+		data[0x10..0x18].copy_from_slice(&u64::MAX.to_le_bytes());
+		let reader = [MemoryRegion::from_vec(0x1000, data, "rw-p", "", "")];
+
+		assert_eq!(follow_pointers(&reader[..], 0x1000, &[0]).unwrap(), 0x1010);
+		assert!(matches!(
+			follow_pointers(&reader[..], 0x1000, &[0, 1]),
+			Err(Error::InvalidPointer { addr: usize::MAX })
+		));
 	}
 
-	Ok(addr + INSTRUCTION_LEN + big_operand as usize)
+	#[test]
+	fn test_read_memory_batch_many() {
+		let data: Vec<u8> = (0..=255).collect();
+		let addr = data.as_ptr() as usize;
+
+		// more ranges than fit into a single syscall, without any of them failing
+		let ranges: Vec<Range<usize>> = (0..2500)
+			.map(|i| addr + i % 256..addr + i % 256 + 1)
+			.collect();
+		let res = read_memory_batch(getpid(), &ranges).unwrap();
+
+		for (i, read) in res.iter().enumerate() {
+			assert_eq!(read.as_deref(), Some(&data[i % 256..i % 256 + 1]), "{}", i);
+		}
+	}
 }
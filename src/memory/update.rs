@@ -1,116 +1,139 @@
 use super::{
 	pattern::{PatternGetter, PatternType},
-	region::read_memory,
+	reader::{MemoryReader, ReadPlan},
 };
 use crate::{
-	memory::region::load_rel_addr,
+	error::{Error, Result},
 	mhw::{
-		data::{GameData, MonsterInfo, PlayerInfo, SessionInfo},
+		data::{BasePointers, GameData, MonsterInfo, PlayerInfo, SessionInfo},
 		offsets,
 	},
 	read_mem_to_type,
 };
-use nix::unistd::Pid;
 use std::str;
 use tracing::{debug, error, trace};
 
-fn get_session_data(pid: Pid, patterns: &[PatternGetter]) -> anyhow::Result<SessionInfo> {
-	// TODO: maybe only copy memory to a buffer with 1 syscall, then read from it, instead of using 4 syscalls?
-
-	let pattern = &patterns[PatternType::LobbyStatus as usize];
+// the patterns don't have to be complete or in any order, so look them up by their type
+fn find_pattern(patterns: &[PatternGetter], pattern_type: PatternType) -> Option<&PatternGetter> {
+	patterns.iter().find(|p| p.pattern_type == pattern_type)
+}
 
+fn get_session_data<R: MemoryReader + ?Sized>(
+	reader: &R,
+	patterns: &[PatternGetter],
+) -> Result<SessionInfo> {
 	let mut info = SessionInfo::default();
 
 	// if the mem_location is none, just return the default SessionInfo, so we can still attempt to check for players and monsters
-	if pattern.mem_location.is_none() {
-		return Ok(info);
-	}
+	let pattern = match find_pattern(patterns, PatternType::LobbyStatus) {
+		Some(pattern) if pattern.mem_location.is_some() => pattern,
+		_ => return Ok(info),
+	};
 
 	trace!("pattern: {:#?}", pattern);
 
-	let start = pattern.mem_location.unwrap().address;
-	// trace!("start: {}", start);
-
-	let pointer = load_rel_addr(pid, start)?;
-
-	// let pointer = read_mem_to_type!(pid, start, u32) as usize;
+	// the session structure the pattern's operand points to, everything is read relative to it
+	let pointer = get_base_pointer(reader, patterns, PatternType::LobbyStatus)?;
 	trace!("pointer: {}", pointer);
 
-	// let rel_pointer = load_rel_addr(pid, pointer)?;
-
-	let debug_ptr = 211829448;
-	let cur_pointer = (pointer + offsets::SESSION_ID) as u32;
-	trace!("cur_pointer: {}", cur_pointer);
-
-	// TODO: fix EFAULT: Bad address
-	// was pointer + offsets::SESSION_ID
-	let mem = read_memory(pid, debug_ptr, offsets::ID_LENGTH)?; // Fails here ATM
-															 // since the game uses UTF-8 this should be safe
+	// the pointer comes from the game and may be garbage
+	let session_id = pointer
+		.checked_add(offsets::SESSION_ID)
+		.ok_or(Error::InvalidPointer { addr: pointer })?;
+	let mem = reader.read(session_id, offsets::ID_LENGTH)?;
+	// since the game uses UTF-8 this should be safe
 	info.session_id = unsafe { str::from_boxed_utf8_unchecked(mem) };
 	trace!("Got session id '{}'", info.session_id);
 
-	let mem = read_memory(
-		pid,
-		pointer as usize + start + offsets::SESSION_HOST_NAME,
-		offsets::PLAYER_NAME_LENGTH,
-	)?;
+	let host_name = pointer
+		.checked_add(offsets::SESSION_HOST_NAME)
+		.ok_or(Error::InvalidPointer { addr: pointer })?;
+	let mem = reader.read(host_name, offsets::PLAYER_NAME_LENGTH)?;
 	// since the game uses UTF-8 this should be safe
 	info.hostname = unsafe { str::from_boxed_utf8_unchecked(mem) };
 	trace!("Got host name");
 
-	// TODO: not working, find out why and fix this
-	let start = pattern.mem_location.unwrap().address;
-	let pointer = read_mem_to_type!(pid, start, u64) as usize;
-	let mem = read_memory(pid, pointer + start + offsets::MISSION_STATUS_OFFSET, 1)?;
-	info.is_mission = mem[0] != 0;
-	trace!("Got mission status");
-
-	// TODO: not working, find out why and fix this
-	let mem = read_memory(pid, pointer + start + offsets::EXPEDITION_STATUS_OFFSET, 1)?;
+	// both flags are read at once, so they can't be from different instants
+	const STATUS_LEN: usize =
+		offsets::MISSION_STATUS_OFFSET - offsets::EXPEDITION_STATUS_OFFSET + 1;
+	// the pointer comes from the game and may be garbage
+	let status = pointer
+		.checked_add(offsets::EXPEDITION_STATUS_OFFSET)
+		.ok_or(Error::InvalidPointer { addr: pointer })?;
+	let mem = reader.read(status, STATUS_LEN)?;
 	info.is_expedition = mem[0] != 0;
-	trace!("Got expedition status");
+	info.is_mission = mem[STATUS_LEN - 1] != 0;
+	trace!("Got mission and expedition status");
 
 	Ok(info)
 }
 
-fn get_damage(pid: Pid, patterns: &[PatternGetter]) -> anyhow::Result<Box<[PlayerInfo]>> {
-	trace!("pid: {}, patterns: {:#?}", pid, patterns);
-	Err(anyhow::anyhow!("not implemented"))
+fn get_damage<R: MemoryReader + ?Sized>(
+	_reader: &R,
+	patterns: &[PatternGetter],
+) -> Result<Box<[PlayerInfo]>> {
+	trace!("patterns: {:#?}", patterns);
+	// TODO: mark the slot of the local player with is_local, the crown collection is kept per local player
+	Err(Error::NotImplemented)
 }
 
-fn get_monster_data(pid: Pid, patterns: &[PatternGetter]) -> anyhow::Result<Box<[MonsterInfo]>> {
-	let pattern = &patterns[PatternType::Monsters as usize];
-
-	let start = pattern.mem_location.unwrap().address;
-	let mem = read_memory(pid, start, 256)?;
+fn get_monster_data<R: MemoryReader + ?Sized>(
+	reader: &R,
+	patterns: &[PatternGetter],
+) -> Result<Box<[MonsterInfo]>> {
+	let start = find_pattern(patterns, PatternType::Monsters)
+		.and_then(|pattern| pattern.mem_location)
+		.ok_or(Error::PatternNotFound(PatternType::Monsters))?
+		.address;
+	let mem = reader.read(start, 256)?;
 
 	debug!("{:02X?}", mem);
 
 	get_single_monster()?;
 
-	Err(anyhow::anyhow!("not implemented"))
+	Err(Error::NotImplemented)
 }
 
-fn get_single_monster() -> anyhow::Result<MonsterInfo> {
-	Err(anyhow::anyhow!("not implemented"))
+fn get_single_monster() -> Result<MonsterInfo> {
+	Err(Error::NotImplemented)
 }
 
-pub fn update_all(
-	pid: Pid,
+// the pointer the rip-relative operand of the pattern points to, which leads to the structure the pattern is about
+fn get_base_pointer<R: MemoryReader + ?Sized>(
+	reader: &R,
+	patterns: &[PatternGetter],
+	pattern_type: PatternType,
+) -> Result<usize> {
+	let target = find_pattern(patterns, pattern_type)
+		.ok_or(Error::PatternNotFound(pattern_type))?
+		.resolve(reader)?;
+
+	Ok(read_mem_to_type!(reader, target, u64) as usize)
+}
+
+pub fn update_all<R: MemoryReader + ?Sized>(
+	reader: &R,
 	patterns: &[PatternGetter],
 	get_monsters: bool,
-) -> anyhow::Result<GameData> {
-	let mut data = GameData::new(get_session_data(pid, patterns)?);
+) -> Result<GameData> {
+	let mut data = GameData::new(get_session_data(reader, patterns)?);
 	debug!("session info: {:#?}", data.session);
 
 	if data.session.is_expedition || data.session.is_mission {
-		match get_damage(pid, patterns) {
+		data.pointers = BasePointers {
+			players: get_base_pointer(reader, patterns, PatternType::PlayerDamage).ok(),
+			monsters: get_monsters
+				.then(|| get_base_pointer(reader, patterns, PatternType::Monsters).ok())
+				.flatten(),
+		};
+
+		match get_damage(reader, patterns) {
 			Ok(damage) => data.players = damage,
 			Err(e) => error!("failed to get player damage: {}", e),
 		}
 
 		if get_monsters {
-			match get_monster_data(pid, patterns) {
+			match get_monster_data(reader, patterns) {
 				Ok(monsters) => data.monsters = monsters,
 				Err(e) => error!("failed to get monster data: {}", e),
 			}
@@ -119,3 +142,40 @@ pub fn update_all(
 
 	Ok(data)
 }
+
+// how often an update is read again before giving up on getting consistent data
+const MAX_ATTEMPTS: usize = 3;
+
+/// like `update_all`, but only returns data that was read with a single batched read and looks plausible
+/// compared to `prev`. if that doesn't work out a few times in a row, `prev` is returned again marked as stale
+pub fn update_consistent<R: MemoryReader + ?Sized>(
+	reader: &R,
+	plan: &mut ReadPlan,
+	patterns: &[PatternGetter],
+	get_monsters: bool,
+	prev: &GameData,
+) -> Result<GameData> {
+	let mut last: Option<GameData> = None;
+
+	for _ in 0..MAX_ATTEMPTS {
+		let data = plan.run(reader, |reader| update_all(reader, patterns, get_monsters))?;
+		let complete = plan.is_complete();
+
+		match data.implausible(prev) {
+			None if complete => return Ok(data),
+			// a surprising change that reads the same twice in a row is real, e.g. a monster that got replaced
+			Some(_) if complete && last.as_ref() == Some(&data) => return Ok(data),
+			reason => debug!(
+				"discarding update: {}",
+				reason.unwrap_or("not everything was read at once")
+			),
+		}
+
+		last = Some(data);
+	}
+
+	Ok(GameData {
+		stale: true,
+		..prev.clone()
+	})
+}
@@ -0,0 +1,279 @@
+// a tiny x86-64 decoder for the instructions our signatures are made of.
+// it only has to know how long an instruction is and where its rip-relative operand points to,
+// so the rest of the instruction is ignored
+
+use super::reader::MemoryReader;
+use crate::error::{Error, Result};
+
+// the longest instruction we can decode: rex, opcode, modrm, sib, disp32, imm32
+const MAX_LEN: usize = 12;
+// enough to know the length of every instruction we can decode
+const HEAD_LEN: usize = 4;
+// how far into a signature operands are looked for
+const MAX_INSTRUCTIONS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+	// mov, lea, cmp, test, ... with a modrm byte
+	ModRm,
+	Call,
+	Jump,
+	Return,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+	pub kind: Kind,
+	pub len: usize,
+	// the absolute address of a [rip+d32] operand or of a relative jump/call
+	pub target: Option<usize>,
+}
+
+// opcodes followed by a modrm byte and no immediate: add, or, and, sub, xor, cmp, movsxd, test, xchg, mov and lea
+fn is_modrm(opcode: u8) -> bool {
+	matches!(
+		opcode,
+		0x01 | 0x03
+			| 0x09 | 0x0B
+			| 0x21 | 0x23
+			| 0x29 | 0x2B
+			| 0x31 | 0x33
+			| 0x39 | 0x3B
+			| 0x63 | 0x85
+			| 0x87 | 0x88..=0x8B | 0x8D
+	)
+}
+
+// bytes of the immediate after the modrm operand, imm32 is sign extended with rex.w
+fn immediate_len(opcode: u8) -> usize {
+	match opcode {
+		0x80 | 0x83 | 0xC6 => 1,
+		0x81 | 0xC7 => 4,
+		_ => 0,
+	}
+}
+
+fn read_i32(bytes: &[u8], at: usize) -> Option<i32> {
+	Some(i32::from_le_bytes(
+		bytes.get(at..at + 4)?.try_into().unwrap(),
+	))
+}
+
+fn relative(next: usize, disp: i32) -> usize {
+	// negative displacements rely on wrapping around
+	next.wrapping_add(disp as isize as usize)
+}
+
+/// decodes the instruction at the start of `bytes`, which is located at `addr`.
+/// None for instructions it doesn't know or if `bytes` is too short
+pub fn decode(bytes: &[u8], addr: usize) -> Option<Instruction> {
+	let rex = bytes
+		.first()
+		.filter(|b| (0x40..=0x4F).contains(*b))
+		.copied();
+	let pos = rex.is_some() as usize;
+	let opcode = *bytes.get(pos)?;
+
+	let branch = |kind, len: usize, disp: i32| Instruction {
+		kind,
+		len,
+		target: Some(relative(addr + len, disp)),
+	};
+
+	match opcode {
+		0xE8 => Some(branch(Kind::Call, pos + 5, read_i32(bytes, pos + 1)?)),
+		0xE9 => Some(branch(Kind::Jump, pos + 5, read_i32(bytes, pos + 1)?)),
+		0xEB | 0x70..=0x7F => Some(branch(
+			Kind::Jump,
+			pos + 2,
+			*bytes.get(pos + 1)? as i8 as i32,
+		)),
+		0xC3 => Some(Instruction {
+			kind: Kind::Return,
+			len: pos + 1,
+			target: None,
+		}),
+		_ if is_modrm(opcode) || immediate_len(opcode) > 0 => {
+			let modrm = *bytes.get(pos + 1)?;
+			let (mode, rm) = (modrm >> 6, modrm & 0b111);
+			let mut len = pos + 2;
+
+			// a sib byte follows when rm is rsp/r12
+			let sib = mode != 0b11 && rm == 0b100;
+			if sib {
+				len += 1;
+			}
+
+			let mut target = None;
+			match mode {
+				0b00 if rm == 0b101 => {
+					let disp = read_i32(bytes, len)?;
+					len += 4;
+					// the displacement is relative to the end of the whole instruction, immediate included
+					let end = len + immediate_len(opcode);
+					target = Some(relative(addr + end, disp));
+				}
+				// [base] with no base register has a disp32 instead
+				0b00 if sib && bytes.get(pos + 2)? & 0b111 == 0b101 => len += 4,
+				0b01 => len += 1,
+				0b10 => len += 4,
+				_ => (),
+			}
+
+			len += immediate_len(opcode);
+			if len > bytes.len() {
+				return None;
+			}
+
+			Some(Instruction {
+				kind: Kind::ModRm,
+				len,
+				target,
+			})
+		}
+		_ => None,
+	}
+}
+
+/// reads and decodes the instruction at `addr`
+pub fn decode_at<R: MemoryReader + ?Sized>(reader: &R, addr: usize) -> Result<Instruction> {
+	let head = match reader.read(addr, HEAD_LEN) {
+		Ok(head) => head,
+		// shorter instructions may be right at the end of a region
+		Err(e) => (1..HEAD_LEN)
+			.rev()
+			.find_map(|len| reader.read(addr, len).ok())
+			.ok_or(e)?,
+	};
+
+	// the length only depends on the first bytes, so the rest can be anything
+	let mut padded = [0; MAX_LEN];
+	padded[..head.len()].copy_from_slice(&head);
+	let len = decode(&padded, addr)
+		.ok_or(Error::UnsupportedInstruction { addr })?
+		.len;
+
+	let bytes = reader.read(addr, len)?;
+	decode(&bytes, addr).ok_or(Error::UnsupportedInstruction { addr })
+}
+
+/// decodes the instructions starting at `addr` and returns where the `n`th rip-relative operand points to,
+/// counting memory operands as well as the targets of calls and jumps
+pub fn resolve_operand<R: MemoryReader + ?Sized>(
+	reader: &R,
+	addr: usize,
+	n: usize,
+) -> Result<usize> {
+	let mut addr = addr;
+	let mut found = 0;
+
+	for _ in 0..MAX_INSTRUCTIONS {
+		let instruction = decode_at(reader, addr)?;
+		if let Some(target) = instruction.target {
+			if found == n {
+				return Ok(target);
+			}
+			found += 1;
+		}
+
+		if instruction.kind == Kind::Return {
+			break;
+		}
+		addr += instruction.len;
+	}
+
+	Err(Error::OperandNotFound { addr, operand: n })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::memory::region::MemoryRegion;
+
+	#[test]
+	fn test_decode() {
+		let cases: &[(&[u8], Kind, usize, Option<usize>)] = &[
+			// mov rcx,[rip+0x10]
+			(
+				&[0x48, 0x8B, 0x0D, 0x10, 0, 0, 0],
+				Kind::ModRm,
+				7,
+				Some(0x1017),
+			),
+			// mov rax,[rip-0x10]
+			(
+				&[0x48, 0x8B, 0x05, 0xF0, 0xFF, 0xFF, 0xFF],
+				Kind::ModRm,
+				7,
+				Some(0xFF7),
+			),
+			// lea rdx,[rsp+0x38]
+			(&[0x48, 0x8D, 0x54, 0x24, 0x38], Kind::ModRm, 5, None),
+			// lea rdx,[rip+0x20]
+			(
+				&[0x48, 0x8D, 0x15, 0x20, 0, 0, 0],
+				Kind::ModRm,
+				7,
+				Some(0x1027),
+			),
+			// mov edx,[rip+0x20] without rex
+			(&[0x8B, 0x15, 0x20, 0, 0, 0], Kind::ModRm, 6, Some(0x1026)),
+			// mov byte [rip+0x20],1, the immediate comes after the displacement
+			(
+				&[0xC6, 0x05, 0x20, 0, 0, 0, 0x01],
+				Kind::ModRm,
+				7,
+				Some(0x1027),
+			),
+			// mov byte [rbx+0x12345678],1
+			(
+				&[0xC6, 0x83, 0x78, 0x56, 0x34, 0x12, 0x01],
+				Kind::ModRm,
+				7,
+				None,
+			),
+			// mov rbx,rax
+			(&[0x48, 0x8B, 0xD8], Kind::ModRm, 3, None),
+			// call -0x100
+			(&[0xE8, 0x00, 0xFF, 0xFF, 0xFF], Kind::Call, 5, Some(0xF05)),
+			// jne +4
+			(&[0x75, 0x04], Kind::Jump, 2, Some(0x1006)),
+			(&[0xC3], Kind::Return, 1, None),
+		];
+
+		for (bytes, kind, len, target) in cases {
+			assert_eq!(
+				decode(bytes, 0x1000),
+				Some(Instruction {
+					kind: *kind,
+					len: *len,
+					target: *target
+				}),
+				"{:02X?}",
+				bytes
+			);
+		}
+
+		// unknown and cut off instructions
+		assert_eq!(decode(&[0x0F, 0x0B], 0x1000), None);
+		assert_eq!(decode(&[0x48, 0x8B, 0x0D, 0x10], 0x1000), None);
+	}
+
+	#[test]
+	fn test_resolve_operand() {
+		// mov rcx,[rip+0x100]; call +0x20; mov rbx,rax; test rax,rax; ret, right at the end of the region
+		let code = vec![
+			0x48, 0x8B, 0x0D, 0x00, 0x01, 0x00, 0x00, 0xE8, 0x20, 0x00, 0x00, 0x00, 0x48, 0x8B,
+			0xD8, 0x48, 0x85, 0xC0, 0xC3,
+		];
+		let reader = [MemoryRegion::from_vec(0x1000, code, "r-xp", "", "")];
+
+		assert_eq!(resolve_operand(&reader[..], 0x1000, 0).unwrap(), 0x1107);
+		assert_eq!(resolve_operand(&reader[..], 0x1000, 1).unwrap(), 0x102C);
+		assert!(matches!(
+			resolve_operand(&reader[..], 0x1000, 2),
+			Err(Error::OperandNotFound { .. })
+		));
+		assert!(resolve_operand(&reader[..], 0x1010, 0).is_err());
+	}
+}
@@ -0,0 +1,204 @@
+// the crowns each character has seen and obtained, kept across hunts in a json file
+
+use super::{
+	data::Crown,
+	events::GameEvent,
+	monster::{MonsterData, MONSTERS},
+};
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Crowns {
+	pub mini: bool,
+	pub silver: bool,
+	pub gold: bool,
+}
+
+impl Crowns {
+	pub fn contains(&self, crown: Crown) -> bool {
+		match crown {
+			Crown::SmallGold => self.mini,
+			Crown::Silver => self.silver,
+			Crown::Gold => self.gold,
+		}
+	}
+
+	// returns if the crown is new
+	fn insert(&mut self, crown: Crown) -> bool {
+		let new = !self.contains(crown);
+		match crown {
+			Crown::SmallGold => self.mini = true,
+			Crown::Silver => self.silver = true,
+			Crown::Gold => self.gold = true,
+		}
+		new
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonsterCrowns {
+	// a monster with this crown appeared in a quest
+	pub seen: Crowns,
+	// a monster with this crown was killed or captured
+	pub obtained: Crowns,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrownCollection {
+	// character name -> monster id -> crowns
+	characters: BTreeMap<Box<str>, BTreeMap<u32, MonsterCrowns>>,
+}
+
+impl CrownCollection {
+	/// reads the collection from `path`, a missing file is an empty collection
+	pub fn load(path: &Path) -> Result<Self> {
+		let content = match fs::read(path) {
+			Ok(content) => content,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+			Err(e) => return Err(e.into()),
+		};
+
+		serde_json::from_slice(&content).map_err(|e| Error::InvalidCollection {
+			path: PathBuf::from(path),
+			reason: e.to_string(),
+		})
+	}
+
+	/// writes the collection to `path` through a temporary file, so a crash can't leave half of it behind
+	pub fn save(&self, path: &Path) -> Result<()> {
+		if let Some(dir) = path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+
+		let json = serde_json::to_vec_pretty(self).map_err(|e| Error::SaveCollection {
+			path: PathBuf::from(path),
+			reason: e.to_string(),
+		})?;
+		let tmp = path.with_extension("tmp");
+		fs::write(&tmp, json)?;
+		fs::rename(&tmp, path)?;
+
+		Ok(())
+	}
+
+	pub fn get(&self, character: &str, id: u32) -> MonsterCrowns {
+		self.characters
+			.get(character)
+			.and_then(|monsters| monsters.get(&id))
+			.copied()
+			.unwrap_or_default()
+	}
+
+	/// records the crown of a monster the event is about, returns if anything new was recorded
+	pub fn record(&mut self, character: &str, event: &GameEvent) -> bool {
+		let (id, crown, obtained) = match event {
+			GameEvent::CrownMonster { id, crown, .. } => (*id, *crown, false),
+			GameEvent::MonsterKilled {
+				id,
+				crown: Some(crown),
+				..
+			}
+			| GameEvent::MonsterCaptured {
+				id,
+				crown: Some(crown),
+				..
+			} => (*id, *crown, true),
+			_ => return false,
+		};
+
+		let crowns = self
+			.characters
+			.entry(Box::from(character))
+			.or_default()
+			.entry(id)
+			.or_default();
+
+		let mut new = crowns.seen.insert(crown);
+		if obtained {
+			new |= crowns.obtained.insert(crown);
+			// a gold crown beats the silver one, so there is no need to hunt for that anymore
+			if crown == Crown::Gold {
+				new |= crowns.obtained.insert(Crown::Silver);
+			}
+		}
+
+		new
+	}
+
+	/// every monster that can have crowns, with what this character already has
+	pub fn progress(&self, character: &str) -> Vec<(&'static MonsterData<'static>, MonsterCrowns)> {
+		MONSTERS
+			.iter()
+			.filter(|monster| monster.is_crownable() == Some(true))
+			.map(|monster| (monster, self.get(character, monster.id)))
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn killed(id: u32, crown: Option<Crown>) -> GameEvent {
+		GameEvent::MonsterKilled {
+			id,
+			name: Box::from("Rathalos"),
+			crown,
+		}
+	}
+
+	#[test]
+	fn test_record() {
+		let mut collection = CrownCollection::default();
+		let seen = GameEvent::CrownMonster {
+			id: 1,
+			name: Box::from("Rathalos"),
+			crown: Crown::Gold,
+		};
+
+		assert!(collection.record("Hunter", &seen));
+		assert!(!collection.record("Hunter", &seen));
+		assert!(!collection.record("Hunter", &killed(1, None)));
+		assert_eq!(collection.get("Hunter", 1).obtained, Crowns::default());
+
+		assert!(collection.record("Hunter", &killed(1, Some(Crown::Gold))));
+		let crowns = collection.get("Hunter", 1);
+		assert!(crowns.seen.gold && !crowns.seen.silver);
+		assert_eq!(
+			crowns.obtained,
+			Crowns {
+				mini: false,
+				silver: true,
+				gold: true
+			}
+		);
+
+		// every character has their own collection
+		assert_eq!(collection.get("Other", 1), MonsterCrowns::default());
+	}
+
+	#[test]
+	fn test_save_load() {
+		let path = std::env::temp_dir().join(format!("crowns-{}.json", std::process::id()));
+		assert_eq!(
+			CrownCollection::load(&path).unwrap(),
+			CrownCollection::default()
+		);
+
+		let mut collection = CrownCollection::default();
+		collection.record("Hunter", &killed(1, Some(Crown::SmallGold)));
+		collection.save(&path).unwrap();
+		let loaded = CrownCollection::load(&path);
+		fs::remove_file(&path).unwrap();
+
+		assert_eq!(loaded.unwrap(), collection);
+	}
+}
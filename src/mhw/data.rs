@@ -1,8 +1,13 @@
 use std::fmt::Display;
 
-use super::monster::{MonsterData, MONSTER_MAP};
+use super::{
+	events::pair_monsters,
+	monster::{MonsterData, MONSTER_MAP},
+};
+use crate::error::{Error, Result};
+use serde::Serialize;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum Crown {
 	SmallGold,
 	Silver,
@@ -19,63 +24,102 @@ impl Display for Crown {
 	}
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct PlayerInfo {
 	pub name: Box<str>,
 	pub damage: usize,
 	pub left_session: bool,
+	// the player of this game, the others joined the session
+	pub is_local: bool,
 }
 
-#[derive(Debug)]
+/// the sizes in cm a monster has to reach for each crown
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CrownSizes {
+	// below this
+	pub mini: f64,
+	// at or above these
+	pub silver: f64,
+	pub gold: f64,
+}
+
+impl CrownSizes {
+	/// None for monsters that can't have crowns
+	pub fn new(monster_data: &MonsterData) -> Option<Self> {
+		let crown_data = monster_data.crown_data()?;
+
+		Some(Self {
+			mini: monster_data.base_size * crown_data.small,
+			silver: monster_data.base_size * crown_data.large,
+			gold: monster_data.base_size * crown_data.very_large,
+		})
+	}
+
+	/// the crown a monster of this size gets, None if it's neither small nor large enough for one
+	pub fn crown(&self, size: f64) -> Option<Crown> {
+		if size < self.mini {
+			Some(Crown::SmallGold)
+		} else if size >= self.gold {
+			Some(Crown::Gold)
+		} else if size >= self.silver {
+			Some(Crown::Silver)
+		} else {
+			None
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct MonsterInfo {
 	pub id: u32,
 	pub name: Box<str>,
 	pub hp: u32,
 	pub max_hp: u32,
+	// in cm
 	pub size: f64,
+	// the size of the monster in percent of the base size of its kind
+	pub size_percent: f64,
+	// None if the monster can't have crowns or their sizes aren't known
+	pub crown_sizes: Option<CrownSizes>,
+	// if the monster can have crowns at all, None if that isn't known
+	pub crownable: Option<bool>,
+	// None if the monster can't have crowns or is of an average size
 	pub crown: Option<Crown>,
+	// None if the monster can't be captured
+	pub capture_hp: Option<u32>,
 }
 
 impl MonsterInfo {
-	pub fn new(id: u32, hp: u32, max_hp: u32, size: f64) -> anyhow::Result<Self> {
+	pub fn new(id: u32, hp: u32, max_hp: u32, size: f64) -> Result<Self> {
 		let monster_data = match MONSTER_MAP.get(&id) {
 			Some(monster) => *monster,
 			None => {
-				return Err(anyhow::anyhow!("Monster not found: {}", id));
+				return Err(Error::UnknownMonster(id));
 			}
 		};
-		let crown = Self::calc_crown(size, monster_data);
+		let crown_sizes = CrownSizes::new(monster_data);
 		Ok(Self {
 			id,
 			name: Box::from(MONSTER_MAP.get(&id).unwrap().name),
 			hp,
 			max_hp,
 			size,
-			crown,
+			size_percent: size / monster_data.base_size * 100.0,
+			crown_sizes,
+			crownable: monster_data.is_crownable(),
+			crown: crown_sizes.and_then(|sizes| sizes.crown(size)),
+			capture_hp: monster_data.capture_hp(max_hp),
 		})
 	}
 
-	fn calc_crown(size: f64, monster_data: &MonsterData) -> Option<Crown> {
-		let small_size = monster_data.base_size * monster_data.crown_data.small;
-		if size < small_size {
-			return Some(Crown::SmallGold);
-		}
-
-		let gold_size = monster_data.base_size * monster_data.crown_data.very_large;
-		if size >= gold_size {
-			return Some(Crown::Gold);
-		}
-
-		let silver_size = monster_data.base_size * monster_data.crown_data.large;
-		if size >= silver_size {
-			return Some(Crown::Silver);
-		}
-
-		None
+	/// if the monster is alive and weak enough to be captured
+	pub fn is_capturable(&self) -> bool {
+		self.capture_hp
+			.is_some_and(|capture_hp| self.hp > 0 && self.hp <= capture_hp)
 	}
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct SessionInfo {
 	pub session_id: Box<str>,
 	pub hostname: Box<str>,
@@ -94,11 +138,22 @@ impl Default for SessionInfo {
 	}
 }
 
-#[derive(Debug, Default)]
+/// where the structures of the game the data was read from start, None if they couldn't be read
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BasePointers {
+	pub players: Option<usize>,
+	pub monsters: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
 pub struct GameData {
 	pub session: SessionInfo,
 	pub players: Box<[PlayerInfo]>,
 	pub monsters: Box<[MonsterInfo]>,
+	#[serde(skip)]
+	pub pointers: BasePointers,
+	// no consistent data could be read, so this is still the data of an earlier update
+	pub stale: bool,
 }
 
 impl GameData {
@@ -117,4 +172,195 @@ impl GameData {
 
 		total
 	}
+
+	/// the player of this game, if it has a slot in the session
+	pub fn local_player(&self) -> Option<&PlayerInfo> {
+		self.players.iter().find(|player| player.is_local)
+	}
+
+	/// why this can't be the data following `prev`, if it looks like some fields were read while the game was writing them
+	pub fn implausible(&self, prev: &GameData) -> Option<&'static str> {
+		if self.monsters.iter().any(|m| m.hp > m.max_hp) {
+			return Some("hp above max hp");
+		}
+
+		let max_hp_changed = pair_monsters(&prev.monsters, &self.monsters)
+			.into_iter()
+			.any(|pair| matches!(pair, (Some(prev), Some(m)) if m.max_hp != prev.max_hp));
+		if max_hp_changed {
+			return Some("max hp changed");
+		}
+
+		if self.session.session_id == prev.session.session_id {
+			// the game only moves its structures between sessions, within one this is a pointer read mid-write
+			let moved = |now: Option<usize>, before: Option<usize>| {
+				now.zip(before).is_some_and(|(now, before)| now != before)
+			};
+			if moved(self.pointers.players, prev.pointers.players) {
+				return Some("player pointer changed");
+			}
+			if moved(self.pointers.monsters, prev.pointers.monsters) {
+				return Some("monster pointer changed");
+			}
+
+			let damage_decreased = self.players.iter().any(|p| {
+				prev.players
+					.iter()
+					.any(|prev| prev.name == p.name && prev.damage > p.damage)
+			});
+			if damage_decreased {
+				return Some("damage went backwards");
+			}
+		}
+
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn monster(hp: u32, max_hp: u32) -> MonsterInfo {
+		MonsterInfo {
+			id: 0,
+			name: Box::from("Anjanath"),
+			hp,
+			max_hp,
+			size: 0.0,
+			size_percent: 0.0,
+			crown_sizes: None,
+			crownable: Some(true),
+			crown: None,
+			capture_hp: None,
+		}
+	}
+
+	fn player(damage: usize) -> PlayerInfo {
+		PlayerInfo {
+			name: Box::from("Hunter"),
+			damage,
+			left_session: false,
+			is_local: true,
+		}
+	}
+
+	#[test]
+	fn test_local_player() {
+		let mut data = GameData {
+			players: Box::new([player(100), player(200)]),
+			..Default::default()
+		};
+		data.players[0].is_local = false;
+		assert_eq!(data.local_player(), Some(&data.players[1]));
+
+		data.players[1].is_local = false;
+		assert_eq!(data.local_player(), None);
+	}
+
+	#[test]
+	fn test_implausible() {
+		let prev = GameData {
+			players: Box::new([player(100)]),
+			monsters: Box::new([monster(500, 1000)]),
+			..Default::default()
+		};
+
+		let mut next = prev.clone();
+		next.players[0].damage = 150;
+		next.monsters[0].hp = 450;
+		assert_eq!(next.implausible(&prev), None);
+
+		next.monsters[0].hp = 1200;
+		assert_eq!(next.implausible(&prev), Some("hp above max hp"));
+
+		next.monsters[0] = monster(500, 2000);
+		assert_eq!(next.implausible(&prev), Some("max hp changed"));
+
+		next.monsters[0] = monster(500, 1000);
+		next.players[0].damage = 50;
+		assert_eq!(next.implausible(&prev), Some("damage went backwards"));
+
+		// damage starts from zero in a new session
+		next.session.session_id = Box::from("NEWSESSION00");
+		assert_eq!(next.implausible(&prev), None);
+	}
+
+	#[test]
+	fn test_implausible_monsters() {
+		let with_id = |id, max_hp| MonsterInfo {
+			id,
+			..monster(500, max_hp)
+		};
+		let prev = GameData {
+			monsters: Box::new([with_id(1, 1000), with_id(2, 3000)]),
+			..Default::default()
+		};
+
+		// the same monsters in another order
+		let mut next = prev.clone();
+		next.monsters.reverse();
+		assert_eq!(next.implausible(&prev), None);
+
+		next.monsters[0].max_hp = 2000;
+		assert_eq!(next.implausible(&prev), Some("max hp changed"));
+	}
+
+	#[test]
+	fn test_implausible_pointers() {
+		let prev = GameData {
+			pointers: BasePointers {
+				players: Some(0x1000),
+				monsters: Some(0x2000),
+			},
+			..Default::default()
+		};
+
+		let mut next = prev.clone();
+		next.pointers.monsters = None;
+		assert_eq!(next.implausible(&prev), None);
+
+		next.pointers.monsters = Some(0x3000);
+		assert_eq!(next.implausible(&prev), Some("monster pointer changed"));
+		next.pointers.players = Some(0x3000);
+		assert_eq!(next.implausible(&prev), Some("player pointer changed"));
+
+		next.session.session_id = Box::from("NEWSESSION00");
+		assert_eq!(next.implausible(&prev), None);
+	}
+
+	#[test]
+	fn test_capture() {
+		let rathalos = MonsterInfo::new(1, 10000, 10000, 1704.22).unwrap();
+		assert_eq!(rathalos.capture_hp, Some(2000));
+		assert!(!rathalos.is_capturable());
+
+		let weak = MonsterInfo {
+			hp: 2000,
+			..rathalos
+		};
+		assert!(weak.is_capturable());
+		assert!(!MonsterInfo { hp: 0, ..weak }.is_capturable());
+
+		// elders can't be tranquilized
+		let fatalis = MonsterInfo::new(101, 100, 10000, 4137.17).unwrap();
+		assert_eq!(fatalis.capture_hp, None);
+		assert!(!fatalis.is_capturable());
+	}
+
+	#[test]
+	fn test_crown_sizes() {
+		let rathalos = MonsterInfo::new(1, 10000, 10000, 1704.22 * 1.2).unwrap();
+		assert!((rathalos.size_percent - 120.0).abs() < 1e-9);
+		assert_eq!(rathalos.crown, Some(Crown::Silver));
+
+		let sizes = rathalos.crown_sizes.unwrap();
+		assert!((sizes.mini - 1533.798).abs() < 1e-3);
+		assert!((sizes.silver - 1959.853).abs() < 1e-3);
+		assert!((sizes.gold - 2096.1906).abs() < 1e-3);
+
+		// fatalis has no known crown sizes
+		let fatalis = MonsterInfo::new(101, 100, 10000, 4137.17).unwrap();
+		assert_eq!(fatalis.crown_sizes, None);
+	}
 }
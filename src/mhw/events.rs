@@ -0,0 +1,314 @@
+use super::data::{Crown, GameData, MonsterInfo};
+use std::fmt::Display;
+
+/// hp percentages that are reported when a monster drops to or below them
+pub const HP_THRESHOLDS: [u32; 3] = [50, 30, 10];
+
+/// something that happened between two updates
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+	QuestStarted,
+	QuestEnded,
+	PlayerJoined {
+		name: Box<str>,
+	},
+	PlayerLeft {
+		name: Box<str>,
+	},
+	MonsterSpawned {
+		id: u32,
+		name: Box<str>,
+	},
+	// left the quest without being killed or captured
+	MonsterDeparted {
+		id: u32,
+		name: Box<str>,
+	},
+	MonsterKilled {
+		id: u32,
+		name: Box<str>,
+		crown: Option<Crown>,
+	},
+	MonsterCaptured {
+		id: u32,
+		name: Box<str>,
+		crown: Option<Crown>,
+	},
+	HpThreshold {
+		id: u32,
+		name: Box<str>,
+		percent: u32,
+	},
+	CrownMonster {
+		id: u32,
+		name: Box<str>,
+		crown: Crown,
+	},
+}
+
+impl Display for GameEvent {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			GameEvent::QuestStarted => write!(f, "quest started"),
+			GameEvent::QuestEnded => write!(f, "quest ended"),
+			GameEvent::PlayerJoined { name } => write!(f, "{} joined", name),
+			GameEvent::PlayerLeft { name } => write!(f, "{} left", name),
+			GameEvent::MonsterSpawned { name, .. } => write!(f, "{} appeared", name),
+			GameEvent::MonsterDeparted { name, .. } => write!(f, "{} departed", name),
+			GameEvent::MonsterKilled { name, .. } => write!(f, "{} was killed", name),
+			GameEvent::MonsterCaptured { name, .. } => write!(f, "{} was captured", name),
+			GameEvent::HpThreshold { name, percent, .. } => {
+				write!(f, "{} is at {}% hp", name, percent)
+			}
+			GameEvent::CrownMonster { name, crown, .. } => {
+				write!(f, "{} is a {} crown", name, crown)
+			}
+		}
+	}
+}
+
+fn hp_percent(monster: &MonsterInfo) -> u32 {
+	match monster.max_hp {
+		0 => 0,
+		max_hp => (monster.hp as u64 * 100 / max_hp as u64) as u32,
+	}
+}
+
+fn in_quest(data: &GameData) -> bool {
+	data.session.is_mission || data.session.is_expedition
+}
+
+// pairs up the monsters of both updates. there is nothing that tells two monsters of the same kind apart,
+// so the n-th one with an id in `prev` is taken to be the n-th one with that id in `next`
+pub(crate) fn pair_monsters<'a>(
+	prev: &'a [MonsterInfo],
+	next: &'a [MonsterInfo],
+) -> Vec<(Option<&'a MonsterInfo>, Option<&'a MonsterInfo>)> {
+	let mut taken = vec![false; next.len()];
+	let mut res = Vec::new();
+
+	for p in prev {
+		let found = (0..next.len()).find(|i| !taken[*i] && next[*i].id == p.id);
+		match found {
+			Some(i) => {
+				taken[i] = true;
+				res.push((Some(p), Some(&next[i])));
+			}
+			None => res.push((Some(p), None)),
+		}
+	}
+
+	res.extend(
+		next.iter()
+			.zip(taken)
+			.filter(|(_, taken)| !taken)
+			.map(|(n, _)| (None, Some(n))),
+	);
+
+	res
+}
+
+/// the events that explain how `prev` turned into `next`
+pub fn diff(prev: &GameData, next: &GameData) -> Vec<GameEvent> {
+	let mut events = Vec::new();
+
+	match (in_quest(prev), in_quest(next)) {
+		(false, true) => events.push(GameEvent::QuestStarted),
+		(true, false) => events.push(GameEvent::QuestEnded),
+		_ => (),
+	}
+
+	for player in next.players.iter() {
+		let before = prev.players.iter().find(|p| p.name == player.name);
+		let name = player.name.clone();
+		match (before.map(|p| p.left_session), player.left_session) {
+			(None | Some(true), false) => events.push(GameEvent::PlayerJoined { name }),
+			(Some(false), true) => events.push(GameEvent::PlayerLeft { name }),
+			_ => (),
+		}
+	}
+	for player in prev.players.iter().filter(|p| !p.left_session) {
+		if !next.players.iter().any(|p| p.name == player.name) {
+			events.push(GameEvent::PlayerLeft {
+				name: player.name.clone(),
+			});
+		}
+	}
+
+	for pair in pair_monsters(&prev.monsters, &next.monsters) {
+		match pair {
+			(None, Some(monster)) => {
+				let (id, name) = (monster.id, monster.name.clone());
+				events.push(GameEvent::MonsterSpawned {
+					id,
+					name: name.clone(),
+				});
+				if let Some(crown) = monster.crown {
+					events.push(GameEvent::CrownMonster { id, name, crown });
+				}
+			}
+			(Some(monster), None) => {
+				let (id, name, crown) = (monster.id, monster.name.clone(), monster.crown);
+				// dead monsters were already reported when their hp hit zero
+				if monster.hp == 0 {
+					continue;
+				}
+				// a monster that vanishes while it could be captured most likely was
+				if in_quest(next) && monster.is_capturable() {
+					events.push(GameEvent::MonsterCaptured { id, name, crown });
+				} else {
+					events.push(GameEvent::MonsterDeparted { id, name });
+				}
+			}
+			(Some(before), Some(monster)) => {
+				let (id, name) = (monster.id, monster.name.clone());
+				if before.hp > 0 && monster.hp == 0 {
+					let crown = monster.crown;
+					events.push(GameEvent::MonsterKilled { id, name, crown });
+					continue;
+				}
+
+				let (before, now) = (hp_percent(before), hp_percent(monster));
+				for percent in HP_THRESHOLDS {
+					if before > percent && now <= percent {
+						events.push(GameEvent::HpThreshold {
+							id,
+							name: name.clone(),
+							percent,
+						});
+					}
+				}
+			}
+			(None, None) => (),
+		}
+	}
+
+	events
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mhw::data::{PlayerInfo, SessionInfo};
+
+	fn monster(id: u32, hp: u32, crown: Option<Crown>) -> MonsterInfo {
+		MonsterInfo {
+			id,
+			name: Box::from(format!("Monster {}", id)),
+			hp,
+			max_hp: 1000,
+			size: 0.0,
+			size_percent: 0.0,
+			crown_sizes: None,
+			crownable: Some(true),
+			crown,
+			capture_hp: Some(200),
+		}
+	}
+
+	fn player(name: &str, left_session: bool) -> PlayerInfo {
+		PlayerInfo {
+			name: Box::from(name),
+			damage: 0,
+			left_session,
+			is_local: false,
+		}
+	}
+
+	fn data(in_quest: bool, players: Vec<PlayerInfo>, monsters: Vec<MonsterInfo>) -> GameData {
+		GameData {
+			session: SessionInfo {
+				is_mission: in_quest,
+				..Default::default()
+			},
+			players: players.into_boxed_slice(),
+			monsters: monsters.into_boxed_slice(),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn test_quest() {
+		let lobby = data(false, vec![player("A", false)], vec![]);
+		let quest = data(
+			true,
+			vec![player("A", false), player("B", false)],
+			vec![monster(1, 1000, Some(Crown::Gold))],
+		);
+
+		assert_eq!(
+			diff(&lobby, &quest),
+			vec![
+				GameEvent::QuestStarted,
+				GameEvent::PlayerJoined {
+					name: Box::from("B")
+				},
+				GameEvent::MonsterSpawned {
+					id: 1,
+					name: Box::from("Monster 1")
+				},
+				GameEvent::CrownMonster {
+					id: 1,
+					name: Box::from("Monster 1"),
+					crown: Crown::Gold
+				},
+			]
+		);
+		assert_eq!(diff(&quest, &quest), vec![]);
+
+		let left = data(true, vec![player("A", false), player("B", true)], vec![]);
+		assert!(diff(&quest, &left).contains(&GameEvent::PlayerLeft {
+			name: Box::from("B")
+		}));
+	}
+
+	#[test]
+	fn test_monsters() {
+		let name = |id| Box::<str>::from(format!("Monster {}", id));
+		let before = data(
+			true,
+			vec![],
+			vec![
+				monster(1, 600, None),
+				monster(2, 100, None),
+				monster(3, 10, None),
+				monster(4, 900, None),
+			],
+		);
+		let after = data(
+			true,
+			vec![],
+			vec![monster(1, 250, None), monster(3, 0, None)],
+		);
+
+		assert_eq!(
+			diff(&before, &after),
+			vec![
+				GameEvent::HpThreshold {
+					id: 1,
+					name: name(1),
+					percent: 50
+				},
+				GameEvent::HpThreshold {
+					id: 1,
+					name: name(1),
+					percent: 30
+				},
+				GameEvent::MonsterCaptured {
+					id: 2,
+					name: name(2),
+					crown: None
+				},
+				GameEvent::MonsterKilled {
+					id: 3,
+					name: name(3),
+					crown: None
+				},
+				GameEvent::MonsterDeparted {
+					id: 4,
+					name: name(4)
+				},
+			]
+		);
+	}
+}
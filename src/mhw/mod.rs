@@ -1,54 +1,16 @@
+pub mod collection;
 pub mod data;
+pub mod events;
 pub mod monster;
 pub mod offsets;
+pub mod process;
 
 use nix::unistd::Pid;
-use std::{
-	fs::{self, read_dir},
-	io::Read,
-};
+use std::path::Path;
 
-const MHW_EXE: &str = "\\MonsterHunterWorld.exe";
+pub use process::find_mhw_pid;
 
-pub fn find_mhw_pid() -> anyhow::Result<Pid> {
-	// read "/proc"
-	let proc = read_dir("/proc")?;
-
-	// iterate over contents
-	for entry in proc {
-		let path = entry?.path();
-
-		// only do smth if its a dir
-		if path.is_dir() && path.file_name().is_some() {
-			let name = match path.file_name().unwrap().to_str() {
-				Some(name) => name,
-				None => continue,
-			};
-
-			// check if dirs name is a pid
-			if !name.chars().all(|c| c.is_ascii_digit()) {
-				continue;
-			}
-
-			// try to open "/proc/pid/cmdline"
-			let mut file = match fs::File::open(format!("{}/cmdline", path.to_string_lossy())) {
-				Ok(file) => file,
-				Err(_) => continue,
-			};
-
-			// try to read the contents of cmdline
-			let mut contents = String::new();
-			if file.read_to_string(&mut contents).is_err() {
-				continue;
-			}
-
-			if contents.contains(MHW_EXE) {
-				// TODO?: implement the simple path logic
-				// is that even necessary?
-				return Ok(Pid::from_raw(name.parse::<i32>()?));
-			}
-		}
-	}
-
-	Err(anyhow::anyhow!("Can't find MH:W pid"))
+// checks whether a process with this pid still exists
+pub fn is_running(pid: Pid) -> bool {
+	Path::new(&format!("/proc/{}", pid)).exists()
 }
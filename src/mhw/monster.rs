@@ -2,9 +2,8 @@ use std::collections::HashMap;
 
 use lazy_static::lazy_static;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum CrownType {
-	Undefined,
 	Standard,
 	Alternate,
 	Savage,
@@ -19,12 +18,15 @@ pub struct CrownData {
 	pub very_large: f64,
 }
 
-const UNDEFINED_CROWN_DATA: CrownData = CrownData {
-	crown_type: CrownType::Undefined,
-	small: 1.0,
-	large: 1.0,
-	very_large: 1.0,
-};
+/// if and at which sizes a monster gets crowns
+#[derive(Debug)]
+pub enum CrownEligibility {
+	Eligible(CrownData),
+	// story, siege and event monsters always have the same size, so the hunter's notes have no crowns for them
+	FixedSize,
+	// the size varies, but the thresholds aren't known yet
+	Unknown,
+}
 
 const STANDARD_CROWN_DATA: CrownData = CrownData {
 	crown_type: CrownType::Standard,
@@ -54,512 +56,617 @@ const RAJANG_CROWN_DATA: CrownData = CrownData {
 	very_large: 1.28,
 };
 
+/// the hp percentage at which monsters start to limp and can be captured, entries that differ set their own
+pub const CAPTURE_PERCENT: u32 = 20;
+
 #[derive(Debug)]
 pub struct MonsterData<'a> {
 	pub str_id: &'a str,
 	pub id: u32,
 	pub base_size: f64,
-	pub crown_data: CrownData,
+	pub crowns: CrownEligibility,
 	pub name: &'a str,
+	// hp percentage at or below which the monster can be captured, None if it can't be tranquilized
+	pub capture_percent: Option<u32>,
 }
 
-const MONSTERS: [MonsterData; 71] = [
+impl MonsterData<'_> {
+	/// the crown thresholds relative to the base size, None if they aren't known or the monster can't have crowns
+	pub fn crown_data(&self) -> Option<&CrownData> {
+		match &self.crowns {
+			CrownEligibility::Eligible(crown_data) => Some(crown_data),
+			CrownEligibility::FixedSize | CrownEligibility::Unknown => None,
+		}
+	}
+
+	/// if the monster can have crowns at all, None if that isn't known
+	pub fn is_crownable(&self) -> Option<bool> {
+		match self.crowns {
+			CrownEligibility::Eligible(_) => Some(true),
+			CrownEligibility::FixedSize => Some(false),
+			CrownEligibility::Unknown => None,
+		}
+	}
+
+	/// the hp at or below which a monster with `max_hp` can be captured
+	pub fn capture_hp(&self, max_hp: u32) -> Option<u32> {
+		self.capture_percent
+			.map(|percent| (max_hp as u64 * percent as u64 / 100) as u32)
+	}
+}
+
+// the ids, base sizes and crown presets are the ones of the original linux-hunter.
+// which monsters have a fixed size follows the hunter's notes, which record no crowns for them.
+// Frostfang Barioth has no preset there, it uses the size record of Barioth (em042_00) and gets its standard crowns,
+// like the other variants that share the base size of their species
+pub const MONSTERS: [MonsterData; 71] = [
 	MonsterData {
 		str_id: "em001_00",
 		id: 9,
 		base_size: 1754.37,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Rathian",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em001_01",
 		id: 10,
 		base_size: 1754.37,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Pink Rathian",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em001_02",
 		id: 88,
 		base_size: 1754.37,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Gold Rathian",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em002_00",
 		id: 1,
 		base_size: 1704.22,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Rathalos",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em002_01",
 		id: 11,
 		base_size: 1704.22,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Azure Rathalos",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em002_02",
 		id: 89,
 		base_size: 1704.22,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Silver Rathalos",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em007_00",
 		id: 12,
 		base_size: 2096.25,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Diablos",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em007_01",
 		id: 13,
 		base_size: 2096.25,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Black Diablos",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em011_00",
 		id: 14,
 		base_size: 536.26,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Kirin",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em018_00",
 		id: 90,
 		base_size: 1389.01,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Yian Garuga",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em018_05",
 		id: 99,
 		base_size: 1389.01,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Scarred Yian Garuga",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em023_00",
 		id: 91,
 		base_size: 829.11,
-		crown_data: RAJANG_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(RAJANG_CROWN_DATA),
 		name: "Rajang",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em023_05",
 		id: 92,
 		base_size: 829.11,
-		crown_data: RAJANG_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(RAJANG_CROWN_DATA),
 		name: "Furious Rajang",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em024_00",
 		id: 16,
 		base_size: 1913.13,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Kushala Daora",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em026_00",
 		id: 17,
 		base_size: 1828.69,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Lunastra",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em027_00",
 		id: 18,
 		base_size: 1790.15,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Teostra",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em032_00",
 		id: 61,
 		base_size: 1943.20,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Tigrex",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em032_01",
 		id: 93,
 		base_size: 1943.20,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Brute Tigrex",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em036_00",
 		id: 19,
 		base_size: 1797.24,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Lavasioth",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em037_00",
 		id: 62,
 		base_size: 1914.74,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Nargacuga",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em042_00",
 		id: 63,
 		base_size: 2098.30,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Barioth",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em043_00",
 		id: 20,
 		base_size: 2063.82,
-		crown_data: ALTERNATE_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(ALTERNATE_CROWN_DATA),
 		name: "Deviljho",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em043_05",
 		id: 64,
 		base_size: 2063.82,
-		crown_data: SAVAGE_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(SAVAGE_CROWN_DATA),
 		name: "Savage Deviljho",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em044_00",
 		id: 21,
 		base_size: 1383.07,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Barroth",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em045_00",
 		id: 22,
 		base_size: 2058.63,
-		crown_data: ALTERNATE_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(ALTERNATE_CROWN_DATA),
 		name: "Uragaan",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em057_00",
 		id: 94,
 		base_size: 1743.49,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Zinogre",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em063_00",
 		id: 65,
 		base_size: 1630.55,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Brachydios",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em063_05",
 		id: 96,
 		base_size: 2282.77,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::FixedSize,
 		name: "Raging Brachydios",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em057_01",
 		id: 95,
 		base_size: 1743.49,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Stygian Zinogre",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em080_00",
 		id: 66,
 		base_size: 2461.50,
-		crown_data: ALTERNATE_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(ALTERNATE_CROWN_DATA),
 		name: "Glavenus",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em080_01",
 		id: 67,
 		base_size: 2372.44,
-		crown_data: ALTERNATE_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(ALTERNATE_CROWN_DATA),
 		name: "Acidic Glavenus",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em100_00",
 		id: 0,
 		base_size: 1646.46,
-		crown_data: ALTERNATE_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(ALTERNATE_CROWN_DATA),
 		name: "Anjanath",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em100_01",
 		id: 68,
 		base_size: 1646.46,
-		crown_data: ALTERNATE_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(ALTERNATE_CROWN_DATA),
 		name: "Fulgur Anjanath",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em101_00",
 		id: 7,
 		base_size: 1109.66,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Great Jagras",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em102_00",
 		id: 24,
 		base_size: 1102.45,
-		crown_data: ALTERNATE_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(ALTERNATE_CROWN_DATA),
 		name: "Pukei Pukei",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em102_01",
 		id: 69,
 		base_size: 1102.45,
-		crown_data: ALTERNATE_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(ALTERNATE_CROWN_DATA),
 		name: "Coral Pukei Pukei",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em103_00",
 		id: 25,
 		base_size: 1848.12,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Nergigante",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em103_05",
 		id: 70,
 		base_size: 1848.12,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Ruiner Nergigante",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em104_00",
 		id: 97,
 		base_size: 4799.78,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::FixedSize,
 		name: "Safi Jiiva",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em105_00",
 		id: 26,
 		base_size: 4509.10,
-		crown_data: UNDEFINED_CROWN_DATA,
+		crowns: CrownEligibility::FixedSize,
 		name: "Xeno Jiiva",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em106_00",
 		id: 4,
 		base_size: 25764.59,
-		crown_data: UNDEFINED_CROWN_DATA,
+		crowns: CrownEligibility::FixedSize,
 		name: "Zorah Magdaros",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em107_00",
 		id: 27,
 		base_size: 901.24,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Kulu Ya Ku",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em108_00",
 		id: 29,
 		base_size: 1508.71,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Jyuratodus",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em109_00",
 		id: 30,
 		base_size: 1300.52,
-		crown_data: ALTERNATE_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(ALTERNATE_CROWN_DATA),
 		name: "Tobi Kadachi",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em109_01",
 		id: 71,
 		base_size: 1300.52,
-		crown_data: ALTERNATE_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(ALTERNATE_CROWN_DATA),
 		name: "Viper Tobi Kadachi",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em110_00",
 		id: 31,
 		base_size: 1143.36,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Paolumu",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em110_01",
 		id: 72,
 		base_size: 1143.36,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Nightshade Paolumu",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em111_00",
 		id: 32,
 		base_size: 1699.75,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Legiana",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em111_05",
 		id: 73,
 		base_size: 1831.69,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Shrieking Legiana",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em112_00",
 		id: 33,
 		base_size: 1053.15,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Great Girros",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em113_00",
 		id: 34,
 		base_size: 1388.75,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Odogaron",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em113_01",
 		id: 74,
 		base_size: 1388.75,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Ebony Odogaron",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em114_00",
 		id: 35,
 		base_size: 1803.47,
-		crown_data: ALTERNATE_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(ALTERNATE_CROWN_DATA),
 		name: "Radobaan",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em115_00",
 		id: 36,
 		base_size: 2095.40,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Vaal Hazak",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em115_05",
 		id: 75,
 		base_size: 2095.40,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Blackveil Vaal Hazak",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em116_00",
 		id: 37,
 		base_size: 1111.11,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Dodogama",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em117_00",
 		id: 38,
 		base_size: 4573.25,
-		crown_data: UNDEFINED_CROWN_DATA,
+		crowns: CrownEligibility::FixedSize,
 		name: "Kulve Taroth",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em118_00",
 		id: 39,
 		base_size: 1928.38,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Bazelgeuse",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em118_05",
 		id: 76,
 		base_size: 1928.38,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Seething Bazelgeuse",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em120_00",
 		id: 28,
 		base_size: 894.04,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Tzitzi Ya Ku",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em121_00",
 		id: 15,
 		base_size: 3423.65,
-		crown_data: UNDEFINED_CROWN_DATA,
+		crowns: CrownEligibility::FixedSize,
 		name: "Behemoth",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em122_00",
 		id: 77,
 		base_size: 1661.99,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Beotodus",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em123_00",
 		id: 78,
 		base_size: 2404.84,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Banbaro",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em124_00",
 		id: 79,
 		base_size: 2596.05,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Velkhana",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em125_00",
 		id: 80,
 		base_size: 2048.25,
-		crown_data: STANDARD_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Namielle",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em126_00",
 		id: 81,
 		base_size: 2910.91,
-		crown_data: UNDEFINED_CROWN_DATA,
+		crowns: CrownEligibility::FixedSize,
 		name: "Shara Ishvalda",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em127_00",
 		id: 23,
 		base_size: 549.70,
-		crown_data: UNDEFINED_CROWN_DATA,
+		crowns: CrownEligibility::FixedSize,
 		name: "Leshen",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em127_01",
 		id: 51,
 		base_size: 633.81,
-		crown_data: UNDEFINED_CROWN_DATA,
+		crowns: CrownEligibility::FixedSize,
 		name: "Ancient Leshen",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em050_00",
 		id: 87,
 		base_size: 2969.63,
-		crown_data: UNDEFINED_CROWN_DATA,
+		crowns: CrownEligibility::FixedSize,
 		name: "Alatreon",
+		capture_percent: None,
 	},
 	MonsterData {
 		str_id: "em042_05",
 		id: 100,
 		base_size: 2098.30,
-		crown_data: UNDEFINED_CROWN_DATA,
+		crowns: CrownEligibility::Eligible(STANDARD_CROWN_DATA),
 		name: "Frostfang Barioth",
+		capture_percent: Some(CAPTURE_PERCENT),
 	},
 	MonsterData {
 		str_id: "em013_00",
 		id: 101,
 		base_size: 4137.17,
-		crown_data: UNDEFINED_CROWN_DATA,
+		crowns: CrownEligibility::FixedSize,
 		name: "Fatalis",
+		capture_percent: None,
 	},
 ];
 
@@ -572,3 +679,101 @@ lazy_static! {
 		map
 	};
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mhw::data::{Crown, MonsterInfo};
+	use std::collections::HashSet;
+
+	#[test]
+	fn test_table() {
+		let mut ids = HashSet::new();
+		let mut str_ids = HashSet::new();
+
+		for monster in MONSTERS.iter() {
+			assert!(ids.insert(monster.id), "duplicate id {}", monster.id);
+			assert!(
+				str_ids.insert(monster.str_id),
+				"duplicate str_id {}",
+				monster.str_id
+			);
+			assert!(
+				sscanf::sscanf!(monster.str_id, "em{u32}_{u32}").is_ok(),
+				"{}",
+				monster.str_id
+			);
+			assert!(monster.base_size > 0.0, "{}", monster.name);
+
+			match &monster.crowns {
+				CrownEligibility::Eligible(crown_data) => {
+					assert!(
+						crown_data.small < 1.0
+							&& 1.0 < crown_data.large
+							&& crown_data.large < crown_data.very_large,
+						"{}",
+						monster.name
+					);
+					// the thresholds have to be the ones of the preset the entry claims to use
+					let preset = match crown_data.crown_type {
+						CrownType::Standard => STANDARD_CROWN_DATA,
+						CrownType::Alternate => ALTERNATE_CROWN_DATA,
+						CrownType::Savage => SAVAGE_CROWN_DATA,
+						CrownType::Rajang => RAJANG_CROWN_DATA,
+					};
+					assert_eq!(
+						(crown_data.small, crown_data.large, crown_data.very_large),
+						(preset.small, preset.large, preset.very_large),
+						"{}",
+						monster.name
+					);
+				}
+				// fixed size monsters are story, siege and event monsters, none of which can be captured
+				CrownEligibility::FixedSize => {
+					assert_eq!(monster.capture_percent, None, "{}", monster.name)
+				}
+				// the table is meant to be complete, so every monster has to be classified
+				CrownEligibility::Unknown => panic!("unknown crowns for {}", monster.name),
+			}
+			if let Some(percent) = monster.capture_percent {
+				assert!(percent > 0 && percent < 100, "{}", monster.name);
+			}
+		}
+
+		assert_eq!(MONSTER_MAP.len(), MONSTERS.len());
+	}
+
+	#[test]
+	fn test_crowns() {
+		for monster in MONSTERS.iter() {
+			let info = |size| MonsterInfo::new(monster.id, 1000, 1000, size).unwrap();
+
+			// an average sized monster never has a crown
+			let average = info(monster.base_size);
+			assert_eq!(average.crown, None, "{}", monster.name);
+			assert_eq!(average.crownable, monster.is_crownable());
+
+			let Some(crown_data) = monster.crown_data() else {
+				assert_eq!(info(monster.base_size * 0.5).crown, None);
+				assert_eq!(info(monster.base_size * 2.0).crown, None);
+				continue;
+			};
+
+			let size = |factor| monster.base_size * factor;
+			assert_eq!(
+				info(size(crown_data.small) - 0.01).crown,
+				Some(Crown::SmallGold),
+				"{}",
+				monster.name
+			);
+			assert_eq!(info(size(crown_data.small)).crown, None);
+			assert_eq!(info(size(crown_data.large)).crown, Some(Crown::Silver));
+			assert_eq!(
+				info(size(crown_data.very_large)).crown,
+				Some(Crown::Gold),
+				"{}",
+				monster.name
+			);
+		}
+	}
+}
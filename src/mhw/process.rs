@@ -0,0 +1,276 @@
+use crate::error::{Error, Result};
+use nix::unistd::Pid;
+use std::{
+	fmt::Display,
+	fs::{self, read_dir},
+	path::Path,
+};
+
+const MHW_EXE: &str = "monsterhunterworld.exe";
+// /proc/<pid>/comm is truncated to 15 characters
+const MHW_COMM: &str = "MonsterHunterWo";
+const MHW_STEAM_APP_ID: &str = "582010";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Evidence {
+	// the game executable is mapped into the process, this is the strongest hint
+	GameImageMapped,
+	Comm,
+	Exe,
+	// argv[0] is the game, not just some argument like with `grep` or a launcher script
+	Argv0,
+	WineEnv,
+}
+
+impl Evidence {
+	fn score(&self) -> u32 {
+		match self {
+			Evidence::GameImageMapped => 8,
+			Evidence::Comm => 4,
+			Evidence::Exe => 4,
+			Evidence::Argv0 => 2,
+			Evidence::WineEnv => 1,
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct Candidate {
+	pub pid: Pid,
+	pub comm: Box<str>,
+	pub cmdline: Box<str>,
+	pub evidence: Vec<Evidence>,
+	// resident memory in pages, used to break ties since the game is by far the biggest process
+	rss: usize,
+}
+
+impl Candidate {
+	pub fn score(&self) -> u32 {
+		self.evidence.iter().map(Evidence::score).sum()
+	}
+}
+
+impl Display for Candidate {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{:>8} {:>5} {:<16} {:<50} {}",
+			self.pid,
+			self.score(),
+			self.comm,
+			format!("{:?}", self.evidence),
+			self.cmdline
+		)
+	}
+}
+
+// checks if the last component of a windows or unix path is the game executable
+fn is_game_path(path: &str) -> bool {
+	path.rsplit(['/', '\\'])
+		.next()
+		.is_some_and(|name| name.to_lowercase() == MHW_EXE)
+}
+
+// the loaders wine and proton start windows programs with, e.g. wine64-preloader
+fn is_wine_path(path: &str) -> bool {
+	path.rsplit('/')
+		.next()
+		.is_some_and(|name| name.starts_with("wine"))
+}
+
+// checks the nul separated environment of a process for the variables wine and proton set
+fn is_wine_env(environ: &str) -> bool {
+	environ.split('\0').any(|var| {
+		var.starts_with("WINEPREFIX=")
+			|| var.starts_with("STEAM_COMPAT_DATA_PATH=")
+			|| var == format!("STEAM_COMPAT_APP_ID={}", MHW_STEAM_APP_ID)
+			|| var == format!("SteamAppId={}", MHW_STEAM_APP_ID)
+	})
+}
+
+fn read_proc_file(dir: &Path, name: &str) -> Option<String> {
+	fs::read(dir.join(name))
+		.ok()
+		.map(|data| String::from_utf8_lossy(&data).into_owned())
+}
+
+fn inspect_process(pid: Pid, dir: &Path) -> Option<Candidate> {
+	let cmdline = read_proc_file(dir, "cmdline")?;
+	let comm = read_proc_file(dir, "comm").unwrap_or_default();
+	let comm = comm.trim_end();
+
+	let mut evidence = Vec::new();
+
+	if comm.starts_with(MHW_COMM) {
+		evidence.push(Evidence::Comm);
+	}
+
+	let exe = fs::read_link(dir.join("exe"))
+		.map(|exe| exe.to_string_lossy().into_owned())
+		.unwrap_or_default();
+	if is_game_path(&exe) {
+		evidence.push(Evidence::Exe);
+	}
+
+	if cmdline.split('\0').next().is_some_and(is_game_path) {
+		evidence.push(Evidence::Argv0);
+	}
+
+	let wine_env = read_proc_file(dir, "environ").is_some_and(|environ| is_wine_env(&environ));
+
+	// without any of the above this is something that mentions the game, or not related to it at all.
+	// a renamed game still runs in wine though, so only those are left to check the mapped executable of
+	if evidence.is_empty() && !wine_env && !is_wine_path(&exe) {
+		return None;
+	}
+
+	// maps can be big, so only read it for processes that could be the game
+	if let Some(maps) = read_proc_file(dir, "maps") {
+		// the path is always the last column, but it may contain spaces
+		if maps.lines().any(|line| is_game_path(line.trim_end())) {
+			evidence.push(Evidence::GameImageMapped);
+		}
+	}
+
+	// wine helpers like the wineserver have neither the game nor its name
+	if evidence.is_empty() {
+		return None;
+	}
+
+	if wine_env {
+		evidence.push(Evidence::WineEnv);
+	}
+
+	let rss = read_proc_file(dir, "statm")
+		.and_then(|statm| statm.split_whitespace().nth(1)?.parse().ok())
+		.unwrap_or(0);
+
+	Some(Candidate {
+		pid,
+		comm: Box::from(comm),
+		cmdline: Box::from(cmdline.replace('\0', " ").trim_end()),
+		evidence,
+		rss,
+	})
+}
+
+/// lists all processes that look like MH:W, the most likely one first
+pub fn find_candidates() -> Result<Vec<Candidate>> {
+	let mut candidates = Vec::new();
+
+	for entry in read_dir("/proc")? {
+		let path = entry?.path();
+
+		// check if dirs name is a pid
+		let pid = match path
+			.file_name()
+			.and_then(|name| name.to_str()?.parse().ok())
+		{
+			Some(pid) => Pid::from_raw(pid),
+			None => continue,
+		};
+
+		if let Some(candidate) = inspect_process(pid, &path) {
+			candidates.push(candidate);
+		}
+	}
+
+	candidates.sort_by(|a, b| b.score().cmp(&a.score()).then(b.rss.cmp(&a.rss)));
+
+	Ok(candidates)
+}
+
+/// the two best candidates if they score the same, so only their size decided which one is used
+pub fn tied_candidates(candidates: &[Candidate]) -> Option<(&Candidate, &Candidate)> {
+	match candidates {
+		[first, second, ..] if first.score() == second.score() => Some((first, second)),
+		_ => None,
+	}
+}
+
+/// reads the steam build id of the game, using the path of the executable mapped into the game
+pub fn game_build(maps: &str) -> Option<String> {
+	let exe = maps
+		.lines()
+		.map(str::trim_end)
+		.find(|line| is_game_path(line))?;
+	// the path starts at the first slash after the inode column
+	let exe = Path::new(&exe[exe.find('/')?..]);
+
+	// <library>/steamapps/common/Monster Hunter World/MonsterHunterWorld.exe
+	let steamapps = exe.ancestors().find(|dir| dir.ends_with("steamapps"))?;
+	let manifest =
+		fs::read_to_string(steamapps.join(format!("appmanifest_{}.acf", MHW_STEAM_APP_ID))).ok()?;
+
+	// the line looks like: "buildid"		"16251013"
+	manifest.lines().find_map(|line| {
+		let value = line.trim().strip_prefix("\"buildid\"")?;
+		Some(value.trim().trim_matches('"').to_string())
+	})
+}
+
+pub fn find_mhw_pid() -> Result<Pid> {
+	match find_candidates()?.first() {
+		Some(candidate) => Ok(candidate.pid),
+		None => Err(Error::ProcessNotFound),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_is_game_path() {
+		assert!(is_game_path(
+			"Z:\\home\\user\\.steam\\steamapps\\common\\Monster Hunter World\\MonsterHunterWorld.exe"
+		));
+		assert!(is_game_path(
+			"/home/user/.steam/steamapps/common/Monster Hunter World/MonsterHunterWorld.exe"
+		));
+		assert!(is_game_path("MonsterHunterWorld.exe"));
+		assert!(!is_game_path("grep"));
+		assert!(!is_game_path("/usr/bin/python3"));
+		assert!(!is_game_path("C:\\MonsterHunterWorld.exe.bak"));
+	}
+
+	#[test]
+	fn test_tied_candidates() {
+		let candidate = |pid, evidence| Candidate {
+			pid: Pid::from_raw(pid),
+			comm: Box::from(""),
+			cmdline: Box::from(""),
+			evidence,
+			rss: 0,
+		};
+
+		let mut candidates = vec![
+			candidate(1, vec![Evidence::GameImageMapped]),
+			candidate(2, vec![Evidence::Comm, Evidence::Exe]),
+		];
+		let (first, second) = tied_candidates(&candidates).unwrap();
+		assert_eq!(
+			(first.pid, second.pid),
+			(Pid::from_raw(1), Pid::from_raw(2))
+		);
+
+		candidates[1].evidence.pop();
+		assert!(tied_candidates(&candidates).is_none());
+		assert!(tied_candidates(&candidates[..1]).is_none());
+	}
+
+	#[test]
+	fn test_wine() {
+		assert!(is_wine_path("/usr/bin/wine64-preloader"));
+		assert!(is_wine_path(
+			"/home/user/.steam/steamapps/common/Proton 8.0/dist/bin/wine-preloader"
+		));
+		assert!(!is_wine_path("/usr/bin/python3"));
+
+		assert!(is_wine_env(
+			"HOME=/home/user\0WINEPREFIX=/home/user/.wine\0"
+		));
+		assert!(is_wine_env("SteamAppId=582010\0"));
+		assert!(!is_wine_env("SteamAppId=1\0HOME=/home/user\0"));
+	}
+}
@@ -0,0 +1,217 @@
+use crate::conf::Config;
+use linux_hunter_lib::{
+	error::Error,
+	memory::{
+		pattern::PatternGetter,
+		reader::{DumpReader, ReadPlan},
+		update::update_consistent,
+	},
+	mhw::{
+		data::GameData,
+		events::{diff, GameEvent},
+		is_running,
+	},
+};
+use nix::unistd::Pid;
+use std::{
+	fs,
+	sync::mpsc::{channel, Receiver, Sender, TryRecvError},
+	thread::{self, sleep},
+	time::{Duration, Instant},
+};
+use tracing::warn;
+
+const DEFAULT_POLL_RATE: f64 = 30.0;
+// intervals from the config are kept in this range, so no setting can make a thread busy-loop or hang
+const MIN_INTERVAL: Duration = Duration::from_millis(1);
+const MAX_INTERVAL: Duration = Duration::from_secs(10);
+
+/// turns a configured interval in seconds into a duration within a sane range
+pub fn clamped_interval(secs: f64) -> Duration {
+	Duration::try_from_secs_f64(secs)
+		.unwrap_or(MAX_INTERVAL)
+		.clamp(MIN_INTERVAL, MAX_INTERVAL)
+}
+
+/// what the memory is read from
+pub enum Source {
+	Game(Pid),
+	Dump(DumpReader),
+}
+
+#[derive(Debug, Clone)]
+pub struct PollSettings {
+	interval: Duration,
+	show_monsters: bool,
+	export: Option<Box<str>>,
+}
+
+impl PollSettings {
+	pub fn new(conf: &Config) -> Self {
+		let rate = conf
+			.run
+			.poll_rate
+			.filter(|rate| *rate > 0.0)
+			.unwrap_or(DEFAULT_POLL_RATE);
+
+		Self {
+			interval: clamped_interval(1.0 / rate),
+			show_monsters: conf.run.show_monsters,
+			export: conf.run.export.clone(),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum PollEvent {
+	// the data and how long it took to read it
+	Data(GameData, Duration),
+	// what changed compared to the previous data
+	Game(GameEvent),
+	// the game exited, so there is nothing to read anymore
+	Lost,
+}
+
+// replaces the file at once, so whoever reads it never sees half of the data
+fn export(path: &str, data: &GameData) -> anyhow::Result<()> {
+	let tmp = format!("{}.tmp", path);
+	fs::write(&tmp, serde_json::to_vec_pretty(data)?)?;
+	fs::rename(&tmp, path)?;
+	Ok(())
+}
+
+/// the polling thread, it stops once this is dropped
+#[derive(Debug)]
+pub struct Poller {
+	pub events: Receiver<PollEvent>,
+	settings: Sender<PollSettings>,
+}
+
+impl Poller {
+	pub fn update_settings(&self, settings: PollSettings) {
+		// fails only if the thread already stopped, which the events channel reports
+		let _ = self.settings.send(settings);
+	}
+}
+
+/// reads the memory of the game in the background at the poll rate and sends the data through the returned channel
+pub fn spawn_poll(source: Source, patterns: Vec<PatternGetter>, settings: PollSettings) -> Poller {
+	let (tx, events) = channel();
+	let (settings_tx, settings_rx) = channel();
+
+	thread::spawn(move || {
+		let mut settings = settings;
+		let mut plan = ReadPlan::default();
+		let mut data = GameData::default();
+
+		loop {
+			let start = Instant::now();
+
+			loop {
+				match settings_rx.try_recv() {
+					Ok(new) => settings = new,
+					Err(TryRecvError::Empty) => break,
+					Err(TryRecvError::Disconnected) => return,
+				}
+			}
+
+			let res = match &source {
+				Source::Game(pid) if !is_running(*pid) => Err(Error::ProcessNotFound),
+				Source::Game(pid) => {
+					update_consistent(pid, &mut plan, &patterns, settings.show_monsters, &data)
+				}
+				Source::Dump(dump) => {
+					update_consistent(dump, &mut plan, &patterns, settings.show_monsters, &data)
+				}
+			};
+
+			// sending only fails if the app stopped listening, so the thread isn't needed anymore
+			match res {
+				Ok(new) => {
+					for event in diff(&data, &new) {
+						if tx.send(PollEvent::Game(event)).is_err() {
+							return;
+						}
+					}
+
+					if let Some(path) = settings.export.as_deref().filter(|_| new != data) {
+						if let Err(e) = export(path, &new) {
+							warn!("failed to export the game data to {}: {}", path, e);
+						}
+					}
+
+					data = new;
+					if tx
+						.send(PollEvent::Data(data.clone(), start.elapsed()))
+						.is_err()
+					{
+						return;
+					}
+				}
+				Err(Error::ProcessNotFound) => {
+					let _ = tx.send(PollEvent::Lost);
+					return;
+				}
+				Err(e) => warn!("failed to update: {}", e),
+			}
+
+			if let Some(remaining) = settings.interval.checked_sub(start.elapsed()) {
+				sleep(remaining);
+			}
+		}
+	});
+
+	Poller {
+		events,
+		settings: settings_tx,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_clamped_interval() {
+		assert_eq!(clamped_interval(0.5), Duration::from_millis(500));
+		// a poll rate of 1e-30 and a refresh of 1e30 ms
+		assert_eq!(clamped_interval(1.0 / 1e-30), MAX_INTERVAL);
+		assert_eq!(clamped_interval(1e30 / 1000.0), MAX_INTERVAL);
+		// a poll rate of inf
+		assert_eq!(clamped_interval(1.0 / f64::INFINITY), MIN_INTERVAL);
+		assert_eq!(clamped_interval(f64::NAN), MAX_INTERVAL);
+	}
+
+	#[test]
+	fn test_poll_dump() {
+		let settings = PollSettings {
+			interval: MIN_INTERVAL,
+			show_monsters: false,
+			export: None,
+		};
+		let poller = spawn_poll(
+			Source::Dump(DumpReader::new(Vec::new())),
+			Vec::new(),
+			settings,
+		);
+
+		// without any patterns there is nothing to read, but the poller keeps sending data
+		for _ in 0..3 {
+			match poller.events.recv_timeout(Duration::from_secs(5)) {
+				Ok(PollEvent::Data(data, _)) => assert_eq!(data, GameData::default()),
+				event => panic!("unexpected {:?}", event),
+			}
+		}
+
+		poller.update_settings(PollSettings {
+			interval: MAX_INTERVAL,
+			show_monsters: true,
+			export: None,
+		});
+		let (events, settings) = (poller.events, poller.settings);
+		drop(settings);
+		// the thread stops once the poller is gone, which closes the events channel
+		while events.recv_timeout(Duration::from_secs(5)).is_ok() {}
+		assert!(matches!(events.try_recv(), Err(TryRecvError::Disconnected)));
+	}
+}
@@ -0,0 +1,84 @@
+use linux_hunter_lib::mhw::collection::{CrownCollection, MonsterCrowns};
+use ratatui::{
+	buffer::Buffer,
+	layout::{Constraint, Rect},
+	style::{Style, Stylize},
+	text::Line,
+	widgets::{Block, Cell, Row, Table, Widget},
+};
+
+/// the crowns of every monster, and which ones the character still needs
+pub struct CrownList<'a> {
+	character: &'a str,
+	collection: &'a CrownCollection,
+	// rows scrolled past
+	scroll: usize,
+}
+
+impl<'a> CrownList<'a> {
+	pub fn new(character: &'a str, collection: &'a CrownCollection, scroll: usize) -> Self {
+		Self {
+			character,
+			collection,
+			scroll,
+		}
+	}
+}
+
+fn crown_cell(seen: bool, obtained: bool) -> Cell<'static> {
+	match (seen, obtained) {
+		(_, true) => Cell::from("obtained").green(),
+		(true, false) => Cell::from("seen").yellow(),
+		(false, false) => Cell::from("needed").dim(),
+	}
+}
+
+impl Widget for &CrownList<'_> {
+	fn render(self, area: Rect, buf: &mut Buffer) {
+		let is_complete = |crowns: &MonsterCrowns| {
+			let obtained = crowns.obtained;
+			obtained.mini && obtained.silver && obtained.gold
+		};
+
+		// complete monsters go to the bottom, since they need no attention anymore
+		let mut progress = self.collection.progress(self.character);
+		progress.sort_by_key(|(_, crowns)| is_complete(crowns));
+		let complete = progress
+			.iter()
+			.filter(|(_, crowns)| is_complete(crowns))
+			.count();
+
+		let rows = progress.iter().skip(self.scroll).map(|(monster, crowns)| {
+			let MonsterCrowns { seen, obtained } = crowns;
+			Row::new(vec![
+				Cell::from(monster.name),
+				crown_cell(seen.mini, obtained.mini),
+				crown_cell(seen.silver, obtained.silver),
+				crown_cell(seen.gold, obtained.gold),
+			])
+		});
+
+		let title = format!(
+			"Crowns of {} - {}/{} monsters complete",
+			self.character,
+			complete,
+			progress.len()
+		);
+		Table::new(
+			rows,
+			[
+				Constraint::Fill(1),
+				Constraint::Length(10),
+				Constraint::Length(10),
+				Constraint::Length(10),
+			],
+		)
+		.header(Row::new(vec!["Monster", "Mini", "Silver", "Gold"]).style(Style::new().bold()))
+		.block(
+			Block::bordered()
+				.title(title)
+				.title_bottom(Line::from("c: back | up/down: scroll").right_aligned()),
+		)
+		.render(area, buf);
+	}
+}
@@ -1,16 +1,24 @@
+mod crowns;
 mod monster;
 mod player;
+mod status;
 
-use crate::conf::Config;
+use crate::{
+	attach::{spawn_attach, AttachEvent},
+	conf::{Config, ConfigWatcher},
+	poll::{clamped_interval, spawn_poll, PollEvent, PollSettings, Poller, Source},
+};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crowns::CrownList;
 use linux_hunter_lib::{
-	memory::{pattern::PatternGetter, update::update_all},
+	memory::{pattern::PatternGetter, reader::DumpReader},
 	mhw::{
+		collection::CrownCollection,
 		data::{GameData, MonsterInfo, PlayerInfo},
 		monster::MONSTER_MAP,
 	},
 };
-use monster::Monster;
+use monster::{Monster, Size};
 use nix::unistd::Pid;
 use player::Player;
 use ratatui::{
@@ -19,96 +27,293 @@ use ratatui::{
 	widgets::{Paragraph, Widget},
 	DefaultTerminal, Frame,
 };
+use status::ScanStatus;
 use std::{
 	io,
+	path::PathBuf,
+	sync::mpsc::{Receiver, TryRecvError},
 	time::{Duration, Instant},
 };
-use tracing::warn;
+use tracing::{info, warn};
+
+// the refresh interval when none is configured, 60 fps
+const DEFAULT_REFRESH: Duration = Duration::from_micros(16_666);
+
+#[derive(Debug)]
+enum GameState {
+	// the game is read in the background
+	Attached(Poller),
+	// the game is not running (anymore) or still being scanned in the background
+	Waiting(Receiver<AttachEvent>, ScanStatus),
+}
 
 #[derive(Debug)]
-pub struct App<'a> {
+pub struct App {
 	exit: bool,
 	mhw_pid: Pid,
-	conf: &'a Config,
+	conf: Config,
+	watcher: Option<ConfigWatcher>,
 	data: GameData,
-	patterns: Vec<PatternGetter>,
-	frametime: f64,
+	state: GameState,
+	// how long it took to read the last data and to draw the last frame
+	poll_time: Duration,
+	render_time: Duration,
+	crowns: CrownCollection,
+	// the name of the last local player seen in a session, whose crowns are recorded
+	character: Option<Box<str>>,
+	// None if the collection can't be saved
+	crowns_path: Option<PathBuf>,
+	show_crown_list: bool,
+	crown_list_scroll: usize,
 }
 
-impl<'a> App<'a> {
-	pub fn new(mhw_pid: Pid, conf: &'a Config, pattern_getters: [PatternGetter; 8]) -> Self {
-		// only get patterns that were actually found and can be used
-		let patterns = pattern_getters
-			.into_iter()
-			.filter(|p| p.mem_location.is_some())
-			.collect();
+impl App {
+	pub fn new(conf: Config) -> Self {
+		let watcher = conf
+			.config_path()
+			.and_then(|path| match ConfigWatcher::new(&path) {
+				Ok(watcher) => Some(watcher),
+				Err(e) => {
+					warn!("can't watch the config file for changes: {}", e);
+					None
+				}
+			});
+
+		let state = GameState::Waiting(
+			spawn_attach(conf.clone(), conf.run.wait),
+			ScanStatus::new(conf.colors.gauge_style()),
+		);
+
+		let (crowns, crowns_path) = match conf.crowns_path() {
+			Some(path) => match CrownCollection::load(&path) {
+				Ok(crowns) => (crowns, Some(path)),
+				// saving would overwrite what couldn't be read
+				Err(e) => {
+					warn!(
+						"failed to load the crown collection, it won't be saved: {}",
+						e
+					);
+					(CrownCollection::default(), None)
+				}
+			},
+			None => {
+				warn!("failed to get the data dir, the crown collection won't be saved");
+				(CrownCollection::default(), None)
+			}
+		};
 
 		Self {
 			conf,
-			mhw_pid,
+			watcher,
+			mhw_pid: Pid::from_raw(0),
 			exit: false,
 			data: GameData::default(),
-			patterns,
-			frametime: 0.0,
+			state,
+			poll_time: Duration::ZERO,
+			render_time: Duration::ZERO,
+			crowns,
+			character: None,
+			crowns_path,
+			show_crown_list: false,
+			crown_list_scroll: 0,
 		}
 	}
 
+	// only get patterns that were actually found and can be used
+	fn found_patterns(pattern_getters: [PatternGetter; 8]) -> Vec<PatternGetter> {
+		pattern_getters
+			.into_iter()
+			.filter(|p| p.mem_location.is_some())
+			.collect()
+	}
+
+	// the character whose crowns are recorded, the setting is only used until the game tells who is playing
+	fn character(&self) -> &str {
+		self.character
+			.as_deref()
+			.unwrap_or_else(|| self.conf.character())
+	}
+
 	/// runs the application's main loop until the user quits
-	pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+	pub fn run(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
 		self.data.players = Box::new([PlayerInfo {
 			name: Box::from("Player 1"),
 			damage: 2500,
 			left_session: false,
+			is_local: false,
 		}]);
 
 		let rathalos = *MONSTER_MAP.get(&1).unwrap();
-		self.data.monsters = Box::new([MonsterInfo {
-			id: 1,
-			name: Box::from(rathalos.name),
-			crown: None,
-			hp: 12586,
-			max_hp: 20600,
-			size: rathalos.base_size,
-		}]);
+		self.data.monsters =
+			Box::new([MonsterInfo::new(1, 12586, 20600, rathalos.base_size).unwrap()]);
 
+		// the screen is only drawn again when something changed
+		let mut redraw = true;
 		while !self.exit {
-			self.main_update_loop();
+			redraw |= self.main_update_loop()?;
+
+			if redraw {
+				let start = Instant::now();
+				terminal.draw(|frame: &mut Frame<'_>| self.draw(frame))?;
+				self.render_time = start.elapsed();
+				redraw = false;
+			}
+
+			redraw |= self.handle_events()?;
+		}
+		Ok(())
+	}
+
+	fn refresh_interval(&self) -> Duration {
+		self.conf
+			.run
+			.refresh
+			.filter(|refresh| *refresh > 0.0)
+			.map_or(DEFAULT_REFRESH, |refresh| {
+				clamped_interval(refresh / 1000.0)
+			})
+	}
+
+	/// handles everything that happened since the last frame, returns if the screen has to be drawn again
+	pub fn main_update_loop(&mut self) -> anyhow::Result<bool> {
+		let mut changed = self.reload_config();
+
+		match &self.state {
+			GameState::Attached(poller) => {
+				let lost = loop {
+					match poller.events.try_recv() {
+						Ok(PollEvent::Data(data, poll_time)) => {
+							if let Some(player) = data.local_player() {
+								self.character = Some(player.name.clone());
+							}
+							self.data = data;
+							self.poll_time = poll_time;
+							changed = true;
+						}
+						Ok(PollEvent::Game(event)) => {
+							info!("{}", event);
+							let character = Box::<str>::from(self.character());
+							// dumps replay old hunts, their crowns were already recorded back then
+							if self.conf.load_dump.is_none()
+								&& self.crowns.record(&character, &event)
+							{
+								if let Some(path) = &self.crowns_path {
+									if let Err(e) = self.crowns.save(path) {
+										warn!("failed to save the crown collection: {}", e);
+									}
+								}
+								changed = true;
+							}
+						}
+						Ok(PollEvent::Lost) => break true,
+						Err(TryRecvError::Empty) => break false,
+						Err(TryRecvError::Disconnected) => {
+							warn!("poll thread died, restarting it");
+							break true;
+						}
+					}
+				};
 
-			terminal.draw(|frame: &mut Frame<'_>| self.draw(frame))?;
+				// dumps have no process behind them, so the poll thread only stops for the game
+				if lost {
+					self.wait_for_game();
+					changed = true;
+				}
+			}
+			// the scan progress changes all the time
+			GameState::Waiting(..) => {
+				self.poll_attach()?;
+				changed = true;
+			}
+		}
+
+		Ok(changed)
+	}
+
+	// handles all progress the attach thread made since the last frame
+	fn poll_attach(&mut self) -> anyhow::Result<()> {
+		let GameState::Waiting(rx, status) = &mut self.state else {
+			return Ok(());
+		};
 
-			if let Some(refresh) = self.conf.refresh {
-				if refresh > self.frametime {
-					std::thread::sleep(Duration::from_millis(
-						(refresh - self.frametime).round() as u64
-					));
+		let attached = loop {
+			match rx.try_recv() {
+				Ok(AttachEvent::Attached(pid, pattern_getters)) => {
+					break Some((pid, pattern_getters))
+				}
+				Ok(AttachEvent::Failed(e)) => return Err(e),
+				Ok(event) => status.update(&event),
+				Err(TryRecvError::Empty) => break None,
+				Err(TryRecvError::Disconnected) => {
+					warn!("attach thread died, restarting it");
+					self.wait_for_game();
+					break None;
 				}
 			}
+		};
 
-			self.handle_events()?;
+		if let Some((pid, pattern_getters)) = attached {
+			info!("attached to pid {}", pid);
+			self.mhw_pid = pid;
+			let source = match &self.conf.load_dump {
+				Some(path) => Source::Dump(DumpReader::open(path)?),
+				None => Source::Game(pid),
+			};
+			let patterns = Self::found_patterns(*pattern_getters);
+			self.state =
+				GameState::Attached(spawn_poll(source, patterns, PollSettings::new(&self.conf)));
 		}
+
 		Ok(())
 	}
 
-	pub fn main_update_loop(&mut self) {
-		let now = Instant::now();
+	// applies changes to the config file without re-scanning the game, returns if anything changed
+	fn reload_config(&mut self) -> bool {
+		if !self
+			.watcher
+			.as_ref()
+			.is_some_and(|watcher| watcher.changed())
+		{
+			return false;
+		}
 
-		match update_all(self.mhw_pid, &self.patterns, self.conf.show_monsters) {
-			Ok(data) => self.data = data,
-			Err(e) => warn!("failed to update: {}", e),
+		match self.conf.reload_profile() {
+			Ok(_) => info!("reloaded the config"),
+			Err(e) => {
+				warn!("failed to reload the config: {}", e);
+				return false;
+			}
 		}
 
-		self.frametime = now.elapsed().as_millis() as f64;
+		if let GameState::Attached(poller) = &self.state {
+			poller.update_settings(PollSettings::new(&self.conf));
+		}
+
+		true
+	}
+
+	fn wait_for_game(&mut self) {
+		warn!("lost pid {}, waiting for the game to restart", self.mhw_pid);
+
+		let mut conf = self.conf.clone();
+		// the game gets a new pid when it restarts
+		conf.mhw_pid = None;
+
+		self.data = GameData::default();
+		let status = ScanStatus::new(conf.colors.gauge_style());
+		self.state = GameState::Waiting(spawn_attach(conf, true), status);
 	}
 
 	fn draw(&self, frame: &mut Frame) {
 		frame.render_widget(self, frame.area());
 	}
 
-	/// updates the application's state based on user input
-	fn handle_events(&mut self) -> io::Result<()> {
-		let has_event = event::poll(Duration::from_millis(100))?;
+	/// updates the application's state based on user input, waiting for it up to the refresh interval.
+	/// returns if there was any input
+	fn handle_events(&mut self) -> io::Result<bool> {
+		let has_event = event::poll(self.refresh_interval())?;
 		if !has_event {
-			return Ok(());
+			return Ok(false);
 		}
 
 		match event::read()? {
@@ -119,12 +324,22 @@ impl<'a> App<'a> {
 			}
 			_ => {}
 		};
-		Ok(())
+		// resizes need a redraw too
+		Ok(true)
 	}
 
 	fn handle_key_event(&mut self, key_event: KeyEvent) {
-		if let KeyCode::Char('q') = key_event.code {
-			self.exit()
+		match key_event.code {
+			KeyCode::Char('q') => self.exit(),
+			KeyCode::Char('c') => self.show_crown_list = !self.show_crown_list,
+			KeyCode::Up | KeyCode::Char('k') if self.show_crown_list => {
+				self.crown_list_scroll = self.crown_list_scroll.saturating_sub(1)
+			}
+			KeyCode::Down | KeyCode::Char('j') if self.show_crown_list => {
+				let rows = self.crowns.progress(self.character()).len();
+				self.crown_list_scroll = (self.crown_list_scroll + 1).min(rows.saturating_sub(1));
+			}
+			_ => (),
 		}
 	}
 
@@ -133,8 +348,19 @@ impl<'a> App<'a> {
 	}
 }
 
-impl<'a> Widget for &'a App<'a> {
+impl Widget for &App {
 	fn render(self, area: Rect, buf: &mut Buffer) {
+		if self.show_crown_list {
+			CrownList::new(self.character(), &self.crowns, self.crown_list_scroll)
+				.render(area, buf);
+			return;
+		}
+
+		if let GameState::Waiting(_, status) = &self.state {
+			status.render(area, buf);
+			return;
+		}
+
 		let mut constraints = Vec::new();
 
 		for _ in 0..8 {
@@ -156,27 +382,49 @@ impl<'a> Widget for &'a App<'a> {
 
 			Player::new(name)
 				.update_damage(player.damage, total_damage)
+				.gauge_style(self.conf.colors.gauge_style())
 				.render(layout[index], buf);
 			index += 1;
 		}
 
 		index = 0;
-		if self.conf.show_monsters {
+		if self.conf.run.show_monsters {
 			for monster in self.data.monsters.iter() {
-				let crown = match self.conf.show_crowns {
+				let crown = match self.conf.run.show_crowns {
 					true => monster.crown,
 					false => None,
 				};
 
-				Monster::new(&monster.name, monster.max_hp, crown)
+				let mut widget = Monster::new(&monster.name, monster.max_hp, crown)
 					.update_hp(monster.hp)
-					.render(layout[4 + index], buf);
+					.capture_hp(monster.capture_hp)
+					.capturable(monster.is_capturable())
+					.gauge_style(self.conf.colors.gauge_style())
+					.capture_style(self.conf.colors.capture_style());
+				if self.conf.run.show_crowns {
+					widget = widget.size(Size {
+						size: monster.size,
+						percent: monster.size_percent,
+						crown_sizes: monster.crown_sizes,
+						crownable: monster.crownable,
+					});
+				}
+				widget.render(layout[4 + index], buf);
 				index += 1;
 			}
 		}
 
-		if self.conf.show_frametime {
-			Paragraph::new(format!("Frametime: {} ms", self.frametime)).render(layout[7], buf);
+		let mut status = Vec::new();
+		if self.conf.run.show_frametime {
+			status.push(format!(
+				"Poll: {:.2} ms | Render: {:.2} ms",
+				self.poll_time.as_secs_f64() * 1000.0,
+				self.render_time.as_secs_f64() * 1000.0
+			));
+		}
+		if self.data.stale {
+			status.push(String::from("Stale data"));
 		}
+		Paragraph::new(status.join(" | ")).render(layout[7], buf);
 	}
 }
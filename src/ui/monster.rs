@@ -1,16 +1,32 @@
-use linux_hunter_lib::mhw::data::Crown;
+use linux_hunter_lib::mhw::data::{Crown, CrownSizes};
 use ratatui::{
 	buffer::Buffer,
 	layout::{Constraint, Direction, Layout, Rect},
-	style::{Style, Stylize},
+	style::{Color, Style, Stylize},
+	text::Line,
 	widgets::{Block, Gauge, Paragraph, Widget},
 };
 
+/// the size of a monster and the sizes it would need for each crown
+pub struct Size {
+	// in cm
+	pub size: f64,
+	pub percent: f64,
+	pub crown_sizes: Option<CrownSizes>,
+	// None if it isn't known if the monster can have crowns
+	pub crownable: Option<bool>,
+}
+
 pub struct Monster<'a> {
 	pub name: &'a str,
 	pub hp: u32,
 	pub max_hp: u32,
 	pub crown: Option<Crown>,
+	pub size: Option<Size>,
+	pub capture_hp: Option<u32>,
+	pub capturable: bool,
+	pub gauge_style: Style,
+	pub capture_style: Style,
 }
 
 impl<'a> Monster<'a> {
@@ -20,6 +36,11 @@ impl<'a> Monster<'a> {
 			max_hp,
 			hp: max_hp,
 			crown,
+			size: None,
+			capture_hp: None,
+			capturable: false,
+			gauge_style: Style::new().white().on_black(),
+			capture_style: Style::new().yellow().on_black(),
 		}
 	}
 
@@ -33,6 +54,56 @@ impl<'a> Monster<'a> {
 
 		self
 	}
+
+	#[must_use = "method moves the value of self and returns the modified value"]
+	pub fn size(mut self, size: Size) -> Self {
+		self.size = Some(size);
+		self
+	}
+
+	#[must_use = "method moves the value of self and returns the modified value"]
+	pub fn capture_hp(mut self, capture_hp: Option<u32>) -> Self {
+		self.capture_hp = capture_hp;
+		self
+	}
+
+	// if the monster can be captured right now
+	#[must_use = "method moves the value of self and returns the modified value"]
+	pub fn capturable(mut self, capturable: bool) -> Self {
+		self.capturable = capturable;
+		self
+	}
+
+	#[must_use = "method moves the value of self and returns the modified value"]
+	pub fn gauge_style(mut self, style: Style) -> Self {
+		self.gauge_style = style;
+		self
+	}
+
+	// the style used once the monster can be captured
+	#[must_use = "method moves the value of self and returns the modified value"]
+	pub fn capture_style(mut self, style: Style) -> Self {
+		self.capture_style = style;
+		self
+	}
+
+	// marks where the capture threshold is on the gauge
+	fn render_capture_marker(&self, area: Rect, buf: &mut Buffer) {
+		let Some(capture_hp) = self.capture_hp else {
+			return;
+		};
+		if self.max_hp == 0 || area.width == 0 {
+			return;
+		}
+
+		let offset = (capture_hp as u64 * area.width as u64 / self.max_hp as u64) as u16;
+		let x = area.x + offset.min(area.width - 1);
+		for y in area.top()..area.bottom() {
+			if let Some(cell) = buf.cell_mut((x, y)) {
+				cell.set_symbol("│");
+			}
+		}
+	}
 }
 
 impl Widget for &Monster<'_> {
@@ -42,6 +113,10 @@ impl Widget for &Monster<'_> {
 			.constraints(vec![
 				Constraint::Percentage(100),
 				Constraint::Min(17),
+				Constraint::Min(match self.size {
+					Some(_) => 22,
+					None => 0,
+				}),
 				Constraint::Min(11),
 			])
 			.split(area);
@@ -54,27 +129,67 @@ impl Widget for &Monster<'_> {
 				Constraint::Min(3),
 				Constraint::Fill(1),
 			]);
-		let sublayout_right = sublayout.clone();
-
 		let sublayout_center = sublayout.split(layout[1]);
-		let sublayout_right = sublayout_right.split(layout[2]);
+		let sublayout_size = sublayout.split(layout[2]);
+		let sublayout_right = sublayout.split(layout[3]);
 
+		let style = match self.capturable {
+			true => self.capture_style,
+			false => self.gauge_style,
+		};
+		// crown candidates stand out, since they are what crown hunting is about
+		let crown_style = self.crown.map(|crown| {
+			let color = match crown {
+				Crown::Silver => Color::Gray,
+				Crown::SmallGold | Crown::Gold => Color::Yellow,
+			};
+			Style::new().fg(color).bold()
+		});
+		let mut block = Block::bordered().title(self.name.to_string());
+		if let Some(style) = crown_style {
+			block = block.border_style(style);
+		}
+		let gauge_area = block.inner(layout[0]);
 		Gauge::default()
-			.block(Block::bordered().title(self.name.to_string()))
-			.gauge_style(Style::new().white().on_black())
+			.block(block)
+			.gauge_style(style)
 			.ratio(self.hp as f64 / self.max_hp as f64)
 			.render(layout[0], buf);
+		self.render_capture_marker(gauge_area, buf);
 
 		let hp_text = format!("HP: {}/{}", self.hp, self.max_hp);
 		Paragraph::new(hp_text)
 			.centered()
 			.render(sublayout_center[1], buf);
 
-		let crown_text = match &self.crown {
-			Some(crown) => format!("{}", crown),
-			None => String::from(""),
+		if let Some(size) = &self.size {
+			let mut lines = vec![Line::from(format!(
+				"{:.0} cm ({:.1}%)",
+				size.size, size.percent
+			))];
+			if let Some(sizes) = &size.crown_sizes {
+				lines.push(Line::from(format!(
+					"M {:.0} S {:.0} G {:.0}",
+					sizes.mini, sizes.silver, sizes.gold
+				)));
+			}
+			Paragraph::new(lines)
+				.centered()
+				.render(sublayout_size[1], buf);
+		}
+
+		// monsters that always have the same size can't have crowns, which is different from an average sized one
+		let not_crownable = self
+			.size
+			.as_ref()
+			.is_some_and(|size| size.crownable == Some(false));
+		let (crown_text, style) = match (&self.crown, not_crownable) {
+			(Some(crown), _) => (format!("{}", crown), crown_style.unwrap_or_default()),
+			(None, true) => (String::from("No crowns"), Style::new().dim()),
+			(None, false) => (String::from(""), Style::new()),
 		};
 		Paragraph::new(crown_text)
+			.style(style)
 			.right_aligned()
 			.render(sublayout_right[1], buf);
 	}
@@ -8,6 +8,7 @@ pub struct Player<'a> {
 	name: &'a str,
 	total_damage: usize,
 	damage_delt: usize,
+	gauge_style: Style,
 }
 
 impl<'a> Player<'a> {
@@ -16,6 +17,7 @@ impl<'a> Player<'a> {
 			name,
 			total_damage: 0,
 			damage_delt: 0,
+			gauge_style: Style::new().white().on_black(),
 		}
 	}
 
@@ -24,6 +26,12 @@ impl<'a> Player<'a> {
 		self.total_damage = total_damage;
 		self
 	}
+
+	#[must_use = "method moves the value of self and returns the modified value"]
+	pub fn gauge_style(mut self, style: Style) -> Self {
+		self.gauge_style = style;
+		self
+	}
 }
 
 impl Widget for &Player<'_> {
@@ -44,7 +52,7 @@ impl Widget for &Player<'_> {
 
 		Gauge::default()
 			.block(Block::bordered().title(self.name.to_string()))
-			.gauge_style(Style::new().white().on_black())
+			.gauge_style(self.gauge_style)
 			.ratio(self.damage_delt as f64 / self.total_damage as f64)
 			.render(layout[0], buf);
 
@@ -0,0 +1,91 @@
+use crate::attach::AttachEvent;
+use linux_hunter_lib::memory::pattern::PatternType;
+use nix::unistd::Pid;
+use ratatui::{
+	buffer::Buffer,
+	layout::{Constraint, Direction, Layout, Rect},
+	style::{Style, Stylize},
+	text::Line,
+	widgets::{Block, Gauge, Paragraph, Widget},
+};
+
+#[derive(Debug, Default)]
+pub struct ScanStatus {
+	pid: Option<Pid>,
+	regions_read: usize,
+	regions_total: usize,
+	patterns: Vec<(PatternType, bool)>,
+	gauge_style: Style,
+}
+
+impl ScanStatus {
+	pub fn new(gauge_style: Style) -> Self {
+		Self {
+			gauge_style,
+			..Default::default()
+		}
+	}
+
+	pub fn update(&mut self, event: &AttachEvent) {
+		match event {
+			AttachEvent::Detecting => *self = Self::new(self.gauge_style),
+			AttachEvent::Scanning(pid) => {
+				*self = Self {
+					pid: Some(*pid),
+					..Self::new(self.gauge_style)
+				}
+			}
+			AttachEvent::RegionsRead { done, total } => {
+				self.regions_read = *done;
+				self.regions_total = *total;
+			}
+			AttachEvent::PatternSearched {
+				pattern_type,
+				found,
+			} => self.patterns.push((*pattern_type, *found)),
+			AttachEvent::Attached(..) | AttachEvent::Failed(_) => (),
+		}
+	}
+}
+
+impl Widget for &ScanStatus {
+	fn render(self, area: Rect, buf: &mut Buffer) {
+		let layout = Layout::default()
+			.direction(Direction::Vertical)
+			.constraints(vec![
+				Constraint::Length(1),
+				Constraint::Length(3),
+				Constraint::Fill(1),
+			])
+			.split(area);
+
+		let detection = match self.pid {
+			Some(pid) => format!("Found MH:W (pid {}), scanning memory...", pid),
+			None => String::from("Waiting for MH:W to start..."),
+		};
+		Paragraph::new(detection).render(layout[0], buf);
+
+		let ratio = match self.regions_total {
+			0 => 0.0,
+			total => self.regions_read as f64 / total as f64,
+		};
+		Gauge::default()
+			.block(Block::bordered().title("Reading memory regions"))
+			.gauge_style(self.gauge_style)
+			.label(format!("{}/{}", self.regions_read, self.regions_total))
+			.ratio(ratio)
+			.render(layout[1], buf);
+
+		let lines: Vec<Line> = self
+			.patterns
+			.iter()
+			.map(|(pattern_type, found)| match found {
+				true => Line::from(format!("{:?}: found", pattern_type)).green(),
+				false => Line::from(format!("{:?}: not found", pattern_type)).red(),
+			})
+			.collect();
+		Paragraph::new(lines)
+			.block(Block::bordered().title("Patterns"))
+			.render(layout[2], buf);
+	}
+}